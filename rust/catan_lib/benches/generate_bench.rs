@@ -3,11 +3,14 @@
 extern crate test;
 extern crate catan_lib;
 
+mod old_generation;
+
 #[cfg(test)]
 pub mod tests {
     use serde_json;
-    use catan_lib::{configuration::MapGenerationSettings, generation::generate_tiles};
+    use catan_lib::{configuration::MapGenerationSettings, generation::{generate_tiles, generate_numbers}};
     use test::Bencher;
+    use super::old_generation;
 
     #[bench]
     pub fn vanilla_generate_bench(b: &mut Bencher) {
@@ -24,4 +27,81 @@ pub mod tests {
             generate_tiles(&config).unwrap()
         });
     }
-}
\ No newline at end of file
+
+    #[bench]
+    pub fn seafarers_generate_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_seafarers();
+        b.iter(|| {
+            generate_tiles(&config).unwrap()
+        });
+    }
+
+    #[bench]
+    pub fn vanilla_numbers_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let grid = generate_tiles(&config).unwrap();
+        b.iter(|| {
+            let mut grid = grid.clone();
+            generate_numbers(&config, &mut grid).unwrap();
+            grid
+        });
+    }
+
+    #[bench]
+    pub fn vanilla56_numbers_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla56();
+        let grid = generate_tiles(&config).unwrap();
+        b.iter(|| {
+            let mut grid = grid.clone();
+            generate_numbers(&config, &mut grid).unwrap();
+            grid
+        });
+    }
+
+    // The same maps with the old generator, to compare against. It can give up, so failures
+    // aren't unwrapped.
+
+    #[bench]
+    pub fn old_vanilla_generate_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla();
+        b.iter(|| {
+            old_generation::generate_tiles(&config).ok()
+        });
+    }
+
+    #[bench]
+    pub fn old_vanilla56_generate_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla56();
+        b.iter(|| {
+            old_generation::generate_tiles(&config).ok()
+        });
+    }
+
+    #[bench]
+    pub fn old_seafarers_generate_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_seafarers();
+        b.iter(|| {
+            old_generation::generate_tiles(&config).ok()
+        });
+    }
+
+    #[bench]
+    pub fn old_vanilla_numbers_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let grid = generate_tiles(&config).unwrap();
+        b.iter(|| {
+            let mut grid = grid.clone();
+            old_generation::generate_numbers(&config, &mut grid).ok()
+        });
+    }
+
+    #[bench]
+    pub fn old_vanilla56_numbers_bench(b: &mut Bencher) {
+        let config = MapGenerationSettings::defaults_vanilla56();
+        let grid = generate_tiles(&config).unwrap();
+        b.iter(|| {
+            let mut grid = grid.clone();
+            old_generation::generate_numbers(&config, &mut grid).ok()
+        });
+    }
+}
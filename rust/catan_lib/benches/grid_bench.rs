@@ -0,0 +1,74 @@
+#![feature(test)]
+
+extern crate test;
+extern crate catan_lib;
+
+#[cfg(test)]
+pub mod tests {
+    use catan_lib::{GameGrid, configuration::MapGenerationSettings, generation::generate_tiles, types};
+    use hexgrid::{DenseHexGrid, Grid, GridMut};
+    use hexgrid::hex_coordinates::{HexCoord, Tile};
+    use test::{Bencher, black_box};
+
+    type DenseGameGrid = DenseHexGrid<types::Tile, types::Edge, types::Corner>;
+
+    fn make_grid() -> GameGrid {
+        generate_tiles(&MapGenerationSettings::defaults_seafarers()).unwrap()
+    }
+
+    /// Look up the tiles around every corner of every tile, which is what scoring corners
+    /// during number generation does.
+    fn corner_lookups<G: Grid<TileData = types::Tile>>(grid: &G, coords: &[Tile]) -> usize {
+        coords.iter()
+            .flat_map(|tile| tile.get_corner_neighbors())
+            .map(|corner| grid.get_tile_neighbors(&corner).len())
+            .sum()
+    }
+
+    #[bench]
+    pub fn hashmap_neighbors_bench(b: &mut Bencher) {
+        let grid = make_grid();
+        let coords = MapGenerationSettings::defaults_seafarers().coords;
+        b.iter(|| {
+            black_box(corner_lookups(&grid, &coords))
+        });
+    }
+
+    #[bench]
+    pub fn dense_neighbors_bench(b: &mut Bencher) {
+        let grid = DenseGameGrid::from(make_grid());
+        let coords = MapGenerationSettings::defaults_seafarers().coords;
+        b.iter(|| {
+            black_box(corner_lookups(&grid, &coords))
+        });
+    }
+
+    #[bench]
+    pub fn hashmap_insert_remove_bench(b: &mut Bencher) {
+        let source = make_grid();
+        let mut grid = GameGrid::new();
+        b.iter(|| {
+            for (coords, data) in source.tiles.iter() {
+                grid.tiles.insert(*coords, *data);
+            }
+            for coords in source.tiles.keys() {
+                grid.tiles.remove(coords);
+            }
+        });
+    }
+
+    #[bench]
+    pub fn dense_insert_remove_bench(b: &mut Bencher) {
+        let source = make_grid();
+        let coords: Vec<Tile> = source.tiles.keys().cloned().collect();
+        let mut grid = DenseGameGrid::bounding(&coords).unwrap();
+        b.iter(|| {
+            for (coords, data) in source.tiles.iter() {
+                grid.insert_tile(*coords, *data);
+            }
+            for coords in source.tiles.keys() {
+                grid.remove_tile(coords);
+            }
+        });
+    }
+}
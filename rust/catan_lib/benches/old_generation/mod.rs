@@ -0,0 +1,159 @@
+//! The map generator from before `generation::solver`, kept so that `generate_bench` can
+//! compare the two. It fills in the coordinates in a fixed order, checks the constraints only
+//! for the value being placed, and gives up after 100 tries of 1000 steps each.
+
+use hexgrid::{hex_coordinates, Grid, GridMut};
+use rand::prelude::*;
+use rand::rngs::OsRng;
+use catan_lib::GameGrid;
+use catan_lib::configuration::MapGenerationSettings;
+use catan_lib::generation::constraints::Constraint;
+use catan_lib::types::{Tile, TileType, Resource};
+
+pub fn generate_tiles(config: &MapGenerationSettings) -> Result<GameGrid, ()> {
+    let mut grid = GameGrid::new();
+    let mut tiles: Vec<TileType> = Vec::new();
+
+    (0 .. config.wheat_count).for_each(|_| tiles.push(TileType::Resource(Resource::Wheat)));
+    (0 .. config.wood_count).for_each(|_| tiles.push(TileType::Resource(Resource::Wood)));
+    (0 .. config.sheep_count).for_each(|_| tiles.push(TileType::Resource(Resource::Sheep)));
+    (0 .. config.stone_count).for_each(|_| tiles.push(TileType::Resource(Resource::Stone)));
+    (0 .. config.clay_count).for_each(|_| tiles.push(TileType::Resource(Resource::Clay)));
+    (0 .. config.gold_count).for_each(|_| tiles.push(TileType::Resource(Resource::Gold)));
+    (0 .. config.desert_count).for_each(|_| tiles.push(TileType::Desert));
+    (0 .. config.ocean_count).for_each(|_| tiles.push(TileType::Ocean));
+
+    let mut rng = OsRng;
+    tiles.shuffle(&mut rng);
+
+    let constraints = config.all_constraints();
+
+    let mut place = |grid: &mut GameGrid, coord: &hex_coordinates::Tile, new_tile_type: &TileType| {
+        if constraints.iter().all(|c| c.allows_tile(config, grid, coord, *new_tile_type)) {
+            grid.insert_tile(*coord, Tile {
+                tile_type: *new_tile_type,
+                number: None,
+                thief: false,
+                faceup: false
+            });
+            Ok(())
+        } else {
+            Err(())
+        }
+    };
+
+    let mut remove = |grid: &mut GameGrid, coord: &hex_coordinates::Tile| {
+        grid.remove_tile(coord);
+    };
+
+    for _ in 0..100 {
+        let mut iterations = 1000;
+        match recurse(&mut grid, config.coords.as_slice(), tiles.as_mut_slice(), &mut iterations, &mut place, &mut remove) {
+            Ok(_) => return Ok(grid),
+            Err(_) => {
+                tiles.shuffle(&mut rng);
+                for coord in config.coords.iter() {
+                    grid.remove_tile(coord);
+                }
+            }
+        }
+    }
+
+    Err(())
+}
+
+pub fn generate_numbers(config: &MapGenerationSettings, grid: &mut GameGrid) -> Result<(), ()> {
+    let mut coords: Vec<hex_coordinates::Tile> = grid.iter_tiles()
+        .filter_map(|(c, d)| match d.tile_type {
+            TileType::Resource(_) => Some(c),
+            _ => None
+        })
+        .collect();
+    let mut numbers = config.numbers.clone();
+
+    let mut rng = OsRng;
+    coords.shuffle(&mut rng);
+
+    let constraints = config.all_constraints();
+
+    let mut place = |grid: &mut GameGrid, coord: &hex_coordinates::Tile, new_num: &i32| {
+        if constraints.iter().all(|c| c.allows_number(config, grid, coord, *new_num)) {
+            match grid.get_tile_mut(coord) {
+                Some(tile) => {
+                    tile.number = Some(*new_num);
+                    Ok(())
+                },
+                None => Err(())
+            }
+        } else {
+            Err(())
+        }
+    };
+
+    let mut remove = |grid: &mut GameGrid, coord: &hex_coordinates::Tile| {
+        if let Some(tile) = grid.get_tile_mut(coord) {
+            tile.number = None;
+        }
+    };
+
+    for _ in 0..100 {
+        let mut iterations = 1000;
+        match recurse(grid, coords.as_slice(), numbers.as_mut_slice(), &mut iterations, &mut place, &mut remove) {
+            Ok(_) => return Ok(()),
+            Err(_) => {
+                numbers.shuffle(&mut rng);
+                for coord in coords.iter() {
+                    remove(grid, coord);
+                }
+            }
+        }
+    }
+
+    Err(())
+}
+
+fn recurse<State, Coord, FillType, P, R>(
+    state: &mut State,
+    coords: &[Coord],
+    fill_data: &mut [FillType],
+    iterations_left: &mut i32,
+    place: &mut P,
+    remove: &mut R
+) -> Result<(), ()>
+where
+    FillType: Clone + Eq,
+    P: FnMut(&mut State, &Coord, &FillType) -> Result<(), ()>,
+    R: FnMut(&mut State, &Coord)
+{
+    if coords.is_empty() || fill_data.is_empty() {
+        return Ok(())
+    }
+
+    let mut data_already_tried: Vec<FillType> = Vec::new();
+    let coord = &coords[0];
+
+    for i in 0..fill_data.len() {
+        *iterations_left -= 1;
+        if *iterations_left <= 0 {
+            return Err(())
+        }
+
+        if data_already_tried.contains(&fill_data[i]) {
+            continue;
+        }
+
+        if place(state, coord, &fill_data[i]).is_ok() {
+            data_already_tried.push(fill_data[i].clone());
+            fill_data.swap(0, i);
+            match recurse(state, &coords[1..], &mut fill_data[1..], iterations_left, place, remove) {
+                Ok(_) => return Ok(()),
+                Err(_) => {
+                    fill_data.swap(0, i);
+                    remove(state, coord);
+                }
+            }
+        }
+    }
+
+    Err(())
+}
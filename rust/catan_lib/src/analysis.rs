@@ -0,0 +1,202 @@
+//! Measure how fair a finished map is, so that generated maps can be compared with each other.
+//!
+//! Everything here is based on pips: the number of dots on a number token, which is the number of
+//! ways (out of 36) to roll that number with two dice.
+
+use std::collections::{HashMap, HashSet};
+use hexgrid::Grid;
+use hexgrid::hex_coordinates::{self, HexCoord};
+use super::types::{Tile, TileType, Resource, Edge, Port, PortResource};
+
+/// The number of pips on a number token. Numbers that can't be rolled have none.
+///
+/// ```
+/// assert_eq!(catan_lib::analysis::pips(6), 5);
+/// assert_eq!(catan_lib::analysis::pips(7), 0);
+/// ```
+pub fn pips(number: i32) -> i32 {
+    match number {
+        2 | 12 => 1,
+        3 | 11 => 2,
+        4 | 10 => 3,
+        5 | 9 => 4,
+        6 | 8 => 5,
+        _ => 0
+    }
+}
+
+/// The total pips of the numbered tiles around a corner, or `None` if fewer than two of them have
+/// numbers. This is what `MapGenerationSettings::min_corner_score` and `max_corner_score` limit.
+pub fn corner_score<G: Grid<TileData = Tile>>(grid: &G, corner: &hex_coordinates::Corner) -> Option<i32> {
+    let numbers: Vec<i32> = corner.get_tile_neighbors()
+        .iter()
+        .filter_map(|c| grid.get_tile(c).and_then(|t| t.number))
+        .collect();
+
+    if numbers.len() < 2 {
+        None
+    } else {
+        Some(numbers.into_iter().map(pips).sum())
+    }
+}
+
+/// A corner where a settlement could be built, meaning it touches at least one tile that isn't
+/// ocean.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spot {
+    pub corner: hex_coordinates::Corner,
+    /// Total pips of the resource tiles around this corner.
+    pub pips: i32,
+    /// The resource of each numbered tile around this corner. A resource is listed twice if two
+    /// of the tiles have it.
+    pub resources: Vec<Resource>,
+    /// A port on one of the edges touching this corner.
+    pub port: Option<Port>,
+}
+
+impl Spot {
+    /// How many resource cards a settlement here gets per roll, on average.
+    pub fn production(&self) -> f64 {
+        self.pips as f64 / 36.0
+    }
+}
+
+/// How useful a port is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortAccess {
+    pub edge: hex_coordinates::Edge,
+    pub port: Port,
+    /// Pips of the better of the two settlement spots at the port.
+    pub spot_pips: i32,
+    /// For a port that trades one resource, the total pips of that resource on the map. A port
+    /// for a resource that is rarely rolled is less useful.
+    pub resource_pips: Option<i32>,
+}
+
+/// A summary of how fair a map is. Create one with `analyze`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    /// Total pips of all tiles of each resource.
+    pub resource_pips: HashMap<Resource, i32>,
+    /// Number of tiles of each resource.
+    pub resource_tiles: HashMap<Resource, u32>,
+    /// Every settlement spot, from the most pips to the fewest.
+    pub spots: Vec<Spot>,
+    /// Every port on the map.
+    pub ports: Vec<PortAccess>,
+    /// How even the map is, from 0 to 1. A map where every resource has the same pips per tile,
+    /// and every corner between numbers has the same corner score, gets 1. See `analyze`.
+    pub balance_index: f64,
+}
+
+impl BalanceReport {
+    /// The `n` spots with the most pips.
+    pub fn best_spots(&self, n: usize) -> &[Spot] {
+        &self.spots[..n.min(self.spots.len())]
+    }
+
+    /// The `n` spots with the fewest pips, starting with the worst.
+    pub fn worst_spots(&self, n: usize) -> Vec<&Spot> {
+        self.spots.iter().rev().take(n).collect()
+    }
+
+    /// Each resource's share of all the pips on the map, from the scarcest to the most common.
+    pub fn scarcity(&self) -> Vec<(Resource, f64)> {
+        let total: i32 = self.resource_pips.values().sum();
+        let mut shares: Vec<(Resource, f64)> = self.resource_pips.iter()
+            .map(|(r, p)| (*r, if total == 0 { 0.0 } else { *p as f64 / total as f64 }))
+            .collect();
+        shares.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        shares
+    }
+}
+
+/// Score a finished map.
+///
+/// The balance index is `1 / (1 + a + b)`, where `a` is the coefficient of variation (standard
+/// deviation divided by mean) of the average pips per tile of each resource, and `b` is the
+/// coefficient of variation of every corner score. So a map where one resource gets all the good
+/// numbers, or where good numbers are clumped together, scores lower.
+pub fn analyze<G>(grid: &G) -> BalanceReport
+where
+    G: Grid<TileData = Tile, EdgeData = Edge>
+{
+    let mut resource_pips: HashMap<Resource, i32> = HashMap::new();
+    let mut resource_tiles: HashMap<Resource, u32> = HashMap::new();
+    let mut corners: HashSet<hex_coordinates::Corner> = HashSet::new();
+
+    for (coord, tile) in grid.iter_tiles() {
+        if let TileType::Resource(resource) = tile.tile_type {
+            *resource_pips.entry(resource).or_insert(0) += tile.number.map_or(0, pips);
+            *resource_tiles.entry(resource).or_insert(0) += 1;
+        }
+        if tile.tile_type != TileType::Ocean {
+            corners.extend(coord.get_corner_neighbors());
+        }
+    }
+
+    let mut spots: Vec<Spot> = corners.iter()
+        .map(|corner| {
+            let mut spot_pips = 0;
+            let mut resources = Vec::new();
+            for tile in corner.get_tile_neighbors().iter().filter_map(|c| grid.get_tile(c)) {
+                if let (TileType::Resource(resource), Some(number)) = (tile.tile_type, tile.number) {
+                    spot_pips += pips(number);
+                    resources.push(resource);
+                }
+            }
+            let port = corner.get_edge_neighbors()
+                .iter()
+                .find_map(|e| grid.get_edge(e).and_then(|e| e.port));
+            Spot { corner: *corner, pips: spot_pips, resources, port }
+        })
+        .collect();
+    // Ties are broken by coordinates, so the same map always gives the same report.
+    spots.sort_by_key(|s| (-s.pips, s.corner.y, s.corner.x, s.corner.label()));
+
+    let mut ports: Vec<PortAccess> = grid.iter_edges()
+        .filter_map(|(edge, data)| data.port.map(|port| (edge, port)))
+        .map(|(edge, port)| PortAccess {
+            edge,
+            port,
+            spot_pips: edge.get_corner_neighbors()
+                .iter()
+                .map(|c| spots.iter().find(|s| s.corner == *c).map_or(0, |s| s.pips))
+                .max()
+                .unwrap_or(0),
+            resource_pips: match port.resource_type {
+                PortResource::Any => None,
+                PortResource::One(resource) => Some(*resource_pips.get(&resource).unwrap_or(&0)),
+            },
+        })
+        .collect();
+    ports.sort_by_key(|p| (p.edge.y, p.edge.x, p.edge.label()));
+
+    let mut pips_per_tile: Vec<f64> = resource_tiles.iter()
+        .map(|(r, n)| resource_pips[r] as f64 / *n as f64)
+        .collect();
+    let mut corner_scores: Vec<f64> = corners.iter()
+        .filter_map(|c| corner_score(grid, c))
+        .map(|s| s as f64)
+        .collect();
+    // Floating point sums depend on the order, and hash maps don't have one.
+    pips_per_tile.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    corner_scores.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let balance_index = 1.0 / (1.0 + variation(&pips_per_tile) + variation(&corner_scores));
+
+    BalanceReport { resource_pips, resource_tiles, spots, ports, balance_index }
+}
+
+/// Coefficient of variation: the standard deviation divided by the mean. Zero if there is nothing
+/// to compare.
+fn variation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt() / mean
+}
@@ -0,0 +1,225 @@
+//! Starting a new game.
+//!
+//! ```
+//! use catan_lib::GamePhase;
+//! use catan_lib::builder::{GameBuilder, PlayerSetup};
+//!
+//! let game = GameBuilder::new()
+//!     .player(PlayerSetup::human("Alice"))
+//!     .player(PlayerSetup::human("Bob").color(0x00ff00))
+//!     .player(PlayerSetup::bot("Robot", "easy"))
+//!     .shuffle_seats(true)
+//!     .seed(7)
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(game.players.len(), 3);
+//! assert_eq!(game.phase, GamePhase::Setup);
+//! ```
+
+use rand::prelude::*;
+use rand::rngs::OsRng;
+use super::{Game, GameGrid, GameID, GamePhase};
+use super::configuration::{Rules, MapGenerationSettings};
+use super::configuration::registry::Variant;
+use super::generation;
+use super::player::{Player, PlayerColor, DEFAULT_COLORS};
+use super::types::DevelopmentCard;
+use super::rng::GameRng;
+use super::dice::Dice;
+use super::cities_and_knights;
+use super::scenarios;
+
+/// One player joining a new game.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlayerSetup {
+    pub name: String,
+    /// If this is `None`, the player gets the first of `DEFAULT_COLORS` that nobody else has.
+    pub color: Option<PlayerColor>,
+    /// See `Player::account`.
+    pub account: Option<String>,
+    /// See `Player::bot`.
+    pub bot: Option<String>,
+}
+
+impl PlayerSetup {
+    pub fn human(name: &str) -> PlayerSetup {
+        PlayerSetup { name: name.to_string(), color: None, account: None, bot: None }
+    }
+
+    /// A player that the bot called `bot` plays for.
+    pub fn bot(name: &str, bot: &str) -> PlayerSetup {
+        PlayerSetup { name: name.to_string(), color: None, account: None, bot: Some(bot.to_string()) }
+    }
+
+    pub fn color(mut self, color: PlayerColor) -> PlayerSetup {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn account(mut self, account: &str) -> PlayerSetup {
+        self.account = Some(account.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Board {
+    Generate(MapGenerationSettings),
+    Grid(GameGrid),
+}
+
+/// Settings for a new game. Everything has a default except the players, so only the players
+/// need to be given. See the module documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameBuilder {
+    id: Option<GameID>,
+    rules: Rules,
+    board: Board,
+    players: Vec<PlayerSetup>,
+    shuffle_seats: bool,
+    seed: Option<u64>,
+}
+
+impl Default for GameBuilder {
+    fn default() -> GameBuilder {
+        GameBuilder::new()
+    }
+}
+
+impl GameBuilder {
+    /// A game of the base game, with a randomly generated map.
+    pub fn new() -> GameBuilder {
+        GameBuilder {
+            id: None,
+            rules: Rules::defaults_vanilla(),
+            board: Board::Generate(MapGenerationSettings::defaults_vanilla()),
+            players: Vec::new(),
+            shuffle_seats: false,
+            seed: None,
+        }
+    }
+
+    /// Use the rules and map settings of a variant, from a `Registry`.
+    pub fn variant(mut self, variant: &Variant) -> GameBuilder {
+        self.rules = variant.rules.clone();
+        self.board = Board::Generate(variant.map.clone());
+        self
+    }
+
+    pub fn rules(mut self, rules: Rules) -> GameBuilder {
+        self.rules = rules;
+        self
+    }
+
+    /// Generate the map from these settings. This replaces any map given with `grid`.
+    pub fn map(mut self, settings: MapGenerationSettings) -> GameBuilder {
+        self.board = Board::Generate(settings);
+        self
+    }
+
+    /// Use a map that is already made, instead of generating one.
+    pub fn grid(mut self, grid: GameGrid) -> GameBuilder {
+        self.board = Board::Grid(grid);
+        self
+    }
+
+    /// Add a player. Players sit in the order they are added, unless `shuffle_seats` is on.
+    pub fn player(mut self, player: PlayerSetup) -> GameBuilder {
+        self.players.push(player);
+        self
+    }
+
+    /// If true, then seat the players in a random order. Either way, each player's ID is the
+    /// order they were added in, starting from 0.
+    pub fn shuffle_seats(mut self, shuffle: bool) -> GameBuilder {
+        self.shuffle_seats = shuffle;
+        self
+    }
+
+    /// Make the game the same every time: the ID (unless it is given), the seats, the map, the
+    /// order of the development cards, and every roll of the dice. The same seed gives the same
+    /// game on any platform, and after updating `rand`. Without a seed, they are all random.
+    pub fn seed(mut self, seed: u64) -> GameBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn id(mut self, id: GameID) -> GameBuilder {
+        self.id = Some(id);
+        self
+    }
+
+    /// Make the game, ready for players to place their first settlements. Fails if there are no
+    /// players, if two players have the same name or color, if there are more players without a
+    /// color than colors left in `DEFAULT_COLORS`, if the map can't be generated, or if the
+    /// scenario can't be set up on it.
+    pub fn build(self) -> Result<Game, String> {
+        if self.players.is_empty() {
+            return Err("A game needs at least one player".to_string());
+        }
+        for (i, player) in self.players.iter().enumerate() {
+            let earlier = &self.players[..i];
+            if earlier.iter().any(|p| p.name == player.name) {
+                return Err(format!("There is more than one player called {}", player.name));
+            }
+            if let Some(color) = player.color.filter(|c| earlier.iter().any(|p| p.color == Some(*c))) {
+                return Err(format!("More than one player has the color #{:06X}", color));
+            }
+        }
+
+        let mut rng = GameRng::seed_from_u64(self.seed.unwrap_or_else(|| OsRng.gen()));
+        let id = self.id.unwrap_or_else(|| rng.gen());
+
+        let mut free_colors = DEFAULT_COLORS.iter()
+            .filter(|c| !self.players.iter().any(|p| p.color == Some(**c)));
+        let mut players = Vec::with_capacity(self.players.len());
+        for (id, setup) in self.players.iter().enumerate() {
+            let color = match setup.color {
+                Some(color) => color,
+                None => *free_colors.next().ok_or("There aren't enough colors for every player")?,
+            };
+            let mut player = Player::new(id as u64, &setup.name, color, &self.rules);
+            player.account = setup.account.clone();
+            player.bot = setup.bot.clone();
+            players.push(player);
+        }
+        if self.shuffle_seats {
+            players.shuffle(&mut rng);
+        }
+
+        let grid = match self.board {
+            Board::Generate(settings) => generation::generate_seeded(&settings, rng.gen())
+                .map_err(|e| e.to_string())?,
+            Board::Grid(grid) => grid,
+        };
+
+        let mut development_cards = Vec::new();
+        for (card, count) in [
+            (DevelopmentCard::Knight, self.rules.devcard_knight_count),
+            (DevelopmentCard::VictoryPoint, self.rules.devcard_vp_count),
+            (DevelopmentCard::YearOfPlenty, self.rules.devcard_yop_count),
+            (DevelopmentCard::Monopoly, self.rules.devcard_monopoly_count),
+            (DevelopmentCard::RoadBuilding, self.rules.devcard_road_count),
+        ] {
+            development_cards.extend(std::iter::repeat(card).take(count as usize));
+        }
+        development_cards.shuffle(&mut rng);
+        let cities_and_knights = cities_and_knights::new_state(&self.rules, &mut rng);
+
+        let mut game = Game {
+            id,
+            players,
+            rules: self.rules,
+            grid,
+            development_cards,
+            phase: GamePhase::Setup,
+            rng: GameRng::seed_from_u64(rng.gen()),
+            dice: Dice::new(),
+            events: vec![],
+            cities_and_knights,
+            scenario: None,
+        };
+        scenarios::setup(&mut game)?;
+        Ok(game)
+    }
+}
@@ -0,0 +1,715 @@
+//! The Cities & Knights expansion. A game uses it when `Rules::cities_and_knights` is set, and
+//! keeps everything that only this expansion needs in `Game::cities_and_knights`.
+//!
+//! * Cities on forests, pastures and mountains collect commodities: see `Game::production`.
+//! * Commodities buy city improvements, in three tracks of five levels each. The first player to
+//!   reach level 4 in a track gets its metropolis, worth two extra points, and a player who reaches
+//!   level 5 takes it from someone who is only at level 4.
+//! * Knights stand on corners, like settlements. They are built, activated, promoted, moved and
+//!   used to displace weaker knights with the functions here.
+//! * Each roll also rolls the event die. A ship moves the barbarians closer, and when they arrive,
+//!   the active knights fight them. A city gate lets players with improvements in that track
+//!   draw progress cards.
+//!
+//! Progress cards are drawn and held, and the ones worth a victory point are counted as soon as
+//! they are drawn, but playing the others isn't supported yet.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+use hexgrid::hex_coordinates::{self, HexCoord};
+use super::{Game, GameGrid};
+use super::configuration::{Rules, CitiesAndKnightsRules};
+use super::events::Event;
+use super::player::{PlayerID, Resources};
+use super::types::{Commodity, Knight, KnightLevel, Purchase, Road, Settlement, TileType};
+
+/// One of the three kinds of city improvement and progress card.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum Track {
+    /// Yellow. Improved with cloth.
+    Trade,
+    /// Blue. Improved with coins.
+    Politics,
+    /// Green. Improved with paper.
+    Science,
+}
+
+impl Track {
+    pub const ALL: [Track; 3] = [Track::Trade, Track::Politics, Track::Science];
+
+    /// The commodity that pays for improvements in this track.
+    pub fn commodity(self) -> Commodity {
+        match self {
+            Track::Trade => Commodity::Cloth,
+            Track::Politics => Commodity::Coin,
+            Track::Science => Commodity::Paper,
+        }
+    }
+}
+
+/// The highest level of city improvement.
+pub const MAX_LEVEL: u32 = 5;
+
+/// The level of city improvement that gets a metropolis, if nobody has it yet.
+pub const METROPOLIS_LEVEL: u32 = 4;
+
+/// The level of politics that lets a player promote knights to mighty.
+pub const MIGHTY_KNIGHT_LEVEL: u32 = 3;
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum ProgressCard {
+    // Trade
+    CommercialHarbor,
+    MasterMerchant,
+    Merchant,
+    MerchantFleet,
+    ResourceMonopoly,
+    TradeMonopoly,
+    // Politics
+    Bishop,
+    Constitution,
+    Deserter,
+    Diplomat,
+    Intrigue,
+    Saboteur,
+    Spy,
+    Warlord,
+    Wedding,
+    // Science
+    Alchemist,
+    Crane,
+    Engineer,
+    Inventor,
+    Irrigation,
+    Medicine,
+    Mining,
+    Printer,
+    RoadBuilding,
+    Smith,
+}
+
+impl ProgressCard {
+    /// True for the cards that are worth a victory point, and are shown as soon as they are drawn
+    /// instead of being held.
+    pub fn is_victory_point(self) -> bool {
+        matches!(self, ProgressCard::Constitution | ProgressCard::Printer)
+    }
+}
+
+/// The progress cards in one track's deck, before shuffling.
+pub fn deck(track: Track) -> Vec<ProgressCard> {
+    let cards: &[(ProgressCard, usize)] = match track {
+        Track::Trade => &[
+            (ProgressCard::CommercialHarbor, 2),
+            (ProgressCard::MasterMerchant, 2),
+            (ProgressCard::Merchant, 6),
+            (ProgressCard::MerchantFleet, 2),
+            (ProgressCard::ResourceMonopoly, 4),
+            (ProgressCard::TradeMonopoly, 2),
+        ],
+        Track::Politics => &[
+            (ProgressCard::Bishop, 2),
+            (ProgressCard::Constitution, 1),
+            (ProgressCard::Deserter, 2),
+            (ProgressCard::Diplomat, 2),
+            (ProgressCard::Intrigue, 2),
+            (ProgressCard::Saboteur, 2),
+            (ProgressCard::Spy, 3),
+            (ProgressCard::Warlord, 2),
+            (ProgressCard::Wedding, 2),
+        ],
+        Track::Science => &[
+            (ProgressCard::Alchemist, 2),
+            (ProgressCard::Crane, 2),
+            (ProgressCard::Engineer, 1),
+            (ProgressCard::Inventor, 2),
+            (ProgressCard::Irrigation, 2),
+            (ProgressCard::Medicine, 2),
+            (ProgressCard::Mining, 2),
+            (ProgressCard::Printer, 1),
+            (ProgressCard::RoadBuilding, 2),
+            (ProgressCard::Smith, 2),
+        ],
+    };
+    cards.iter().flat_map(|(card, count)| std::iter::repeat(*card).take(*count)).collect()
+}
+
+/// A face of the event die. Half of the faces are ships.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum EventDieFace {
+    Ship,
+    Gate(Track),
+}
+
+impl EventDieFace {
+    pub fn roll<R: Rng>(rng: &mut R) -> EventDieFace {
+        match rng.gen_range(0, 6) {
+            0..=2 => EventDieFace::Ship,
+            3 => EventDieFace::Gate(Track::Trade),
+            4 => EventDieFace::Gate(Track::Politics),
+            _ => EventDieFace::Gate(Track::Science),
+        }
+    }
+}
+
+/// A player's level in each track of city improvements, from 0 to `MAX_LEVEL`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Improvements {
+    pub trade: u32,
+    pub politics: u32,
+    pub science: u32,
+}
+
+impl Improvements {
+    pub fn level(&self, track: Track) -> u32 {
+        match track {
+            Track::Trade => self.trade,
+            Track::Politics => self.politics,
+            Track::Science => self.science,
+        }
+    }
+
+    fn level_mut(&mut self, track: Track) -> &mut u32 {
+        match track {
+            Track::Trade => &mut self.trade,
+            Track::Politics => &mut self.politics,
+            Track::Science => &mut self.science,
+        }
+    }
+}
+
+/// Everything about a game that only matters in Cities & Knights.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    /// How many times the barbarian ship has moved since it last attacked.
+    pub barbarian_position: u32,
+    /// The thief stays in the desert until the barbarians have attacked once.
+    pub barbarians_have_attacked: bool,
+    pub improvements: BTreeMap<PlayerID, Improvements>,
+    /// The city that has the metropolis for each track, if anyone has reached it.
+    pub metropolises: BTreeMap<Track, hex_coordinates::Corner>,
+    /// The progress cards left to draw, with the top of each deck last.
+    pub decks: BTreeMap<Track, Vec<ProgressCard>>,
+    /// The progress cards that each player is holding.
+    pub progress_cards: BTreeMap<PlayerID, Vec<ProgressCard>>,
+    /// Victory points from progress cards that are worth one.
+    pub progress_points: BTreeMap<PlayerID, u32>,
+    /// Victory points for being the best defender against the barbarians.
+    pub defender_points: BTreeMap<PlayerID, u32>,
+}
+
+impl State {
+    /// The state at the start of a game, with shuffled progress card decks.
+    pub fn new<R: Rng>(rng: &mut R) -> State {
+        let decks = Track::ALL.iter()
+            .map(|track| {
+                let mut cards = deck(*track);
+                cards.shuffle(rng);
+                (*track, cards)
+            })
+            .collect();
+        State {
+            barbarian_position: 0,
+            barbarians_have_attacked: false,
+            improvements: BTreeMap::new(),
+            metropolises: BTreeMap::new(),
+            decks,
+            progress_cards: BTreeMap::new(),
+            progress_points: BTreeMap::new(),
+            defender_points: BTreeMap::new(),
+        }
+    }
+
+    pub fn improvements(&self, player: PlayerID) -> Improvements {
+        self.improvements.get(&player).cloned().unwrap_or_default()
+    }
+}
+
+/// How a barbarian attack went.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AttackOutcome {
+    /// The knights won. If one player's knights were strongest, they get a point as the defender
+    /// of Catan. Otherwise, each player who tied for strongest can draw a progress card from a
+    /// track of their choice with `draw_progress_card`.
+    Defended { defender: Option<PlayerID>, tied: Vec<PlayerID> },
+    /// The barbarians won. Each of these players must pick a city to lose with `pillage_city`.
+    Pillaged { players: Vec<PlayerID> },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Attack {
+    /// How many cities there are, which is how strong the barbarians are.
+    pub strength: u32,
+    /// The total strength of every active knight.
+    pub defense: u32,
+    pub outcome: AttackOutcome,
+}
+
+/// Everything that happened in one roll. See `roll`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Roll {
+    /// The two numbers on the dice. The first one is the red die.
+    pub dice: [u32; 2],
+    pub event: EventDieFace,
+    pub attack: Option<Attack>,
+    /// The players who drew a progress card because of a city gate.
+    pub drew_progress_cards: Vec<PlayerID>,
+    /// What each player collected.
+    pub production: BTreeMap<PlayerID, Resources>,
+}
+
+fn settings(game: &Game) -> Result<&CitiesAndKnightsRules, String> {
+    game.rules.cities_and_knights.as_ref().ok_or_else(|| "This game isn't using Cities & Knights".to_string())
+}
+
+fn state(game: &Game) -> Result<&State, String> {
+    game.cities_and_knights.as_ref().ok_or_else(|| "This game isn't using Cities & Knights".to_string())
+}
+
+fn state_mut(game: &mut Game) -> Result<&mut State, String> {
+    game.cities_and_knights.as_mut().ok_or_else(|| "This game isn't using Cities & Knights".to_string())
+}
+
+/// Take `price` from `player`, or fail if they can't afford it.
+fn pay(game: &mut Game, player: PlayerID, price: &Resources) -> Result<(), String> {
+    let p = game.players.iter_mut()
+        .find(|p| p.id() == player)
+        .ok_or_else(|| format!("There is no player {}", player))?;
+    p.resources = p.resources.checked_sub(price)
+        .ok_or_else(|| format!("{} can't afford that", p.name))?;
+    Ok(())
+}
+
+fn check_affordable(game: &Game, player: PlayerID, price: &Resources) -> Result<(), String> {
+    let p = game.players.iter()
+        .find(|p| p.id() == player)
+        .ok_or_else(|| format!("There is no player {}", player))?;
+    if p.resources.contains(price) {
+        Ok(())
+    } else {
+        Err(format!("{} can't afford that", p.name))
+    }
+}
+
+fn cities(grid: &GameGrid, player: PlayerID) -> Vec<hex_coordinates::Corner> {
+    let mut cities: Vec<_> = grid.corners.iter()
+        .filter(|(_, c)| c.settlement == Settlement::City(player))
+        .map(|(coords, _)| *coords)
+        .collect();
+    cities.sort_by_key(|c| (c.y, c.x, c.to_string()));
+    cities
+}
+
+/// Roll the dice and the event die for `player`, and do everything that they cause: move the
+/// barbarians (and fight them, if they arrive), hand out progress cards for a city gate, and
+/// give everyone what they collect.
+pub fn roll(game: &mut Game, player: PlayerID) -> Result<Roll, String> {
+    settings(game)?;
+    let dice = game.roll_dice(player);
+    let event = EventDieFace::roll(&mut game.rng);
+    game.events.push(Event::EventDie { player, face: event });
+
+    let mut attack = None;
+    let mut drew_progress_cards = Vec::new();
+    match event {
+        EventDieFace::Ship => {
+            let length = settings(game)?.barbarian_track_length;
+            let state = state_mut(game)?;
+            state.barbarian_position += 1;
+            if state.barbarian_position >= length {
+                attack = Some(barbarians_attack(game)?);
+            }
+        },
+        EventDieFace::Gate(track) => {
+            // Players draw in turn order, starting with the one who rolled.
+            let seat = game.players.iter().position(|p| p.id() == player).unwrap_or(0);
+            let mut order: Vec<PlayerID> = game.players.iter().map(|p| p.id()).collect();
+            order.rotate_left(seat);
+            for p in order {
+                let level = state(game)?.improvements(p).level(track);
+                if level > 0 && dice[0] <= level + 1 && draw_progress_card(game, p, track)?.is_some() {
+                    drew_progress_cards.push(p);
+                }
+            }
+        },
+    }
+
+    let total = dice[0] + dice[1];
+    let production = if total == 7 { BTreeMap::new() } else { game.collect(total) };
+    Ok(Roll { dice, event, attack, drew_progress_cards, production })
+}
+
+/// Fight the barbarians now. `roll` calls this when the barbarian ship arrives. Afterwards, the
+/// ship starts again from the beginning, and every knight is deactivated.
+pub fn barbarians_attack(game: &mut Game) -> Result<Attack, String> {
+    settings(game)?;
+    let strength = game.grid.corners.values()
+        .filter(|c| matches!(c.settlement, Settlement::City(_)))
+        .count() as u32;
+    let mut defense_by_player: BTreeMap<PlayerID, u32> = game.players.iter().map(|p| (p.id(), 0)).collect();
+    for knight in game.grid.corners.values().filter_map(|c| c.knight).filter(|k| k.active) {
+        *defense_by_player.entry(knight.owner).or_insert(0) += knight.level.strength();
+    }
+    let defense = defense_by_player.values().sum();
+
+    let outcome = if defense >= strength {
+        let best = defense_by_player.values().cloned().max().unwrap_or(0);
+        let tied: Vec<PlayerID> = defense_by_player.iter()
+            .filter(|(_, d)| **d == best)
+            .map(|(p, _)| *p)
+            .collect();
+        if tied.len() == 1 {
+            *state_mut(game)?.defender_points.entry(tied[0]).or_insert(0) += 1;
+            AttackOutcome::Defended { defender: Some(tied[0]), tied: vec![] }
+        } else {
+            AttackOutcome::Defended { defender: None, tied }
+        }
+    } else {
+        // Only players with a city that can be pillaged can lose one.
+        let metropolises: HashSet<hex_coordinates::Corner> = state(game)?.metropolises.values().cloned().collect();
+        let exposed: Vec<PlayerID> = defense_by_player.keys()
+            .filter(|p| cities(&game.grid, **p).iter().any(|c| !metropolises.contains(c)))
+            .cloned()
+            .collect();
+        let weakest = exposed.iter().map(|p| defense_by_player[p]).min().unwrap_or(0);
+        let players = exposed.into_iter().filter(|p| defense_by_player[p] == weakest).collect();
+        AttackOutcome::Pillaged { players }
+    };
+
+    for corner in game.grid.corners.values_mut() {
+        if let Some(knight) = corner.knight.as_mut() {
+            knight.active = false;
+        }
+    }
+    let state = state_mut(game)?;
+    state.barbarian_position = 0;
+    state.barbarians_have_attacked = true;
+
+    let attack = Attack { strength, defense, outcome };
+    game.events.push(Event::BarbarianAttack(attack.clone()));
+    Ok(attack)
+}
+
+/// After the barbarians win, turn one of `player`'s cities back into a settlement. It can't be a
+/// city with a metropolis.
+pub fn pillage_city(game: &mut Game, player: PlayerID, city: &hex_coordinates::Corner) -> Result<(), String> {
+    if state(game)?.metropolises.values().any(|c| c == city) {
+        return Err("A city with a metropolis can't be pillaged".to_string());
+    }
+    match game.grid.corners.get_mut(city) {
+        Some(corner) if corner.settlement == Settlement::City(player) => corner.settlement = Settlement::Settlement(player),
+        _ => return Err(format!("Player {} has no city at {}", player, city)),
+    }
+    if let Some(p) = game.players.iter_mut().find(|p| p.id() == player) {
+        p.cities += 1;
+        p.settlements = p.settlements.saturating_sub(1);
+    }
+    Ok(())
+}
+
+/// Draw the top progress card of `track` for `player`. A card worth a victory point is counted
+/// straight away instead of being held. Returns `None`, without drawing, if the deck is empty or
+/// the player's hand is full.
+pub fn draw_progress_card(game: &mut Game, player: PlayerID, track: Track) -> Result<Option<ProgressCard>, String> {
+    let limit = settings(game)?.progress_card_limit as usize;
+    let state = state_mut(game)?;
+    if state.progress_cards.get(&player).map_or(0, Vec::len) >= limit {
+        return Ok(None);
+    }
+    let card = match state.decks.get_mut(&track).and_then(Vec::pop) {
+        Some(card) => card,
+        None => return Ok(None),
+    };
+    if card.is_victory_point() {
+        *state.progress_points.entry(player).or_insert(0) += 1;
+    } else {
+        state.progress_cards.entry(player).or_default().push(card);
+    }
+    game.events.push(Event::ProgressCardDrawn { player, track });
+    Ok(Some(card))
+}
+
+/// Improve one of `player`'s city improvement tracks by a level, paying as many of the track's
+/// commodity as the new level. The player needs at least one city.
+///
+/// If this earns the track's metropolis, it goes on `metropolis`, which has to be one of the
+/// player's cities that doesn't have a metropolis yet. Returns true if the player got the
+/// metropolis.
+pub fn improve(
+    game: &mut Game,
+    player: PlayerID,
+    track: Track,
+    metropolis: Option<hex_coordinates::Corner>
+) -> Result<bool, String> {
+    settings(game)?;
+    let level = state(game)?.improvements(player).level(track) + 1;
+    if level > MAX_LEVEL {
+        return Err(format!("{:?} is already at the highest level", track));
+    }
+    let player_cities = cities(&game.grid, player);
+    if player_cities.is_empty() {
+        return Err("City improvements need a city".to_string());
+    }
+    let price = Resources::of(track.commodity(), level);
+    check_affordable(game, player, &price)?;
+
+    // Work out who has the metropolis before changing anything, so that nothing changes if the
+    // chosen city isn't allowed.
+    let state = state(game)?;
+    let holder = state.metropolises.get(&track)
+        .and_then(|c| match game.grid.corners.get(c).map(|c| c.settlement) {
+            Some(Settlement::City(owner)) => Some(owner),
+            _ => None,
+        });
+    let earns_metropolis = match holder {
+        None => level >= METROPOLIS_LEVEL,
+        Some(holder) => holder != player && level == MAX_LEVEL && state.improvements(holder).level(track) < MAX_LEVEL,
+    };
+    let free_cities: Vec<_> = player_cities.into_iter()
+        .filter(|c| !state.metropolises.values().any(|m| m == c))
+        .collect();
+    let metropolis = if earns_metropolis && !free_cities.is_empty() {
+        match metropolis {
+            Some(city) if free_cities.contains(&city) => Some(city),
+            Some(city) => return Err(format!("The metropolis can't go on {}", city)),
+            None => return Err("Pick a city for the metropolis".to_string()),
+        }
+    } else {
+        None
+    };
+
+    pay(game, player, &price)?;
+    let state = state_mut(game)?;
+    *state.improvements.entry(player).or_default().level_mut(track) = level;
+    if let Some(city) = metropolis {
+        state.metropolises.insert(track, city);
+    }
+    Ok(metropolis.is_some())
+}
+
+/// The victory points that Cities & Knights gives `player` on top of the base game's: two for
+/// each metropolis, and one for each progress card and defender point worth one. This is 0 when
+/// playing without it. `Game::victory_points` adds these in.
+pub fn bonus_victory_points(game: &Game, player: PlayerID) -> u32 {
+    match &game.cities_and_knights {
+        Some(state) => {
+            let metropolises = state.metropolises.values()
+                .filter(|c| game.grid.corners.get(c).map(|c| c.settlement) == Some(Settlement::City(player)))
+                .count() as u32;
+            2 * metropolises +
+                state.progress_points.get(&player).cloned().unwrap_or(0) +
+                state.defender_points.get(&player).cloned().unwrap_or(0)
+        },
+        None => 0,
+    }
+}
+
+/// True if the thief can be moved. In Cities & Knights, it stays where it is until the first
+/// barbarian attack.
+pub fn thief_can_move(game: &Game) -> bool {
+    game.cities_and_knights.as_ref().map_or(true, |s| s.barbarians_have_attacked)
+}
+
+fn knight_count(game: &Game, player: PlayerID, level: KnightLevel) -> u32 {
+    game.grid.corners.values()
+        .filter_map(|c| c.knight)
+        .filter(|k| k.owner == player && k.level == level)
+        .count() as u32
+}
+
+fn knights_available(game: &Game, player: PlayerID, level: KnightLevel) -> Result<bool, String> {
+    let settings = settings(game)?;
+    let limit = match level {
+        KnightLevel::Basic => settings.basic_knight_count,
+        KnightLevel::Strong => settings.strong_knight_count,
+        KnightLevel::Mighty => settings.mighty_knight_count,
+    };
+    Ok(knight_count(game, player, level) < limit)
+}
+
+fn is_empty(grid: &GameGrid, corner: &hex_coordinates::Corner) -> bool {
+    grid.corners.get(corner).map_or(true, |c| c.settlement == Settlement::None && c.knight.is_none())
+}
+
+fn is_on_land(grid: &GameGrid, corner: &hex_coordinates::Corner) -> bool {
+    corner.get_tile_neighbors().iter()
+        .any(|t| grid.tiles.get(t).map_or(false, |t| t.tile_type != TileType::Ocean))
+}
+
+fn has_road(grid: &GameGrid, edge: &hex_coordinates::Edge, player: PlayerID) -> bool {
+    grid.edges.get(edge).map_or(false, |e| e.road == Road::Road(player))
+}
+
+/// True if something of another player's is on this corner, which blocks `player`'s roads.
+fn is_blocked(grid: &GameGrid, corner: &hex_coordinates::Corner, player: PlayerID) -> bool {
+    match grid.corners.get(corner) {
+        Some(c) => {
+            let building = match c.settlement {
+                Settlement::Settlement(owner) | Settlement::City(owner) => Some(owner),
+                Settlement::None => None,
+            };
+            building.into_iter().chain(c.knight.map(|k| k.owner)).any(|owner| owner != player)
+        },
+        None => false,
+    }
+}
+
+/// The corners that `player` can reach from `start` along their own roads, not counting `start`.
+/// Other players' settlements and knights block the way.
+fn reachable(grid: &GameGrid, start: &hex_coordinates::Corner, player: PlayerID) -> HashSet<hex_coordinates::Corner> {
+    let mut seen = HashSet::new();
+    seen.insert(*start);
+    let mut queue = VecDeque::new();
+    queue.push_back(*start);
+    while let Some(corner) = queue.pop_front() {
+        if corner != *start && is_blocked(grid, &corner, player) {
+            continue;
+        }
+        for edge in corner.get_edge_neighbors() {
+            if !has_road(grid, &edge, player) {
+                continue;
+            }
+            for next in edge.get_corner_neighbors() {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    seen.remove(start);
+    seen
+}
+
+fn knight_at(game: &Game, corner: &hex_coordinates::Corner, player: PlayerID) -> Result<Knight, String> {
+    match game.grid.corners.get(corner).and_then(|c| c.knight) {
+        Some(knight) if knight.owner == player => Ok(knight),
+        _ => Err(format!("Player {} has no knight at {}", player, corner)),
+    }
+}
+
+fn set_knight(game: &mut Game, corner: &hex_coordinates::Corner, knight: Option<Knight>) {
+    game.grid.corners.entry(*corner).or_default().knight = knight;
+}
+
+/// Build a new basic knight for `player`. It starts inactive, on an empty corner next to one of
+/// the player's roads.
+pub fn build_knight(game: &mut Game, player: PlayerID, corner: &hex_coordinates::Corner) -> Result<(), String> {
+    if !knights_available(game, player, KnightLevel::Basic)? {
+        return Err("There are no basic knights left".to_string());
+    }
+    if !is_empty(&game.grid, corner) || !is_on_land(&game.grid, corner) {
+        return Err(format!("A knight can't go on {}", corner))
+    }
+    if !corner.get_edge_neighbors().iter().any(|e| has_road(&game.grid, e, player)) {
+        return Err(format!("{} isn't next to one of player {}'s roads", corner, player));
+    }
+    let price = game.rules.cost(Purchase::Knight).clone();
+    pay(game, player, &price)?;
+    set_knight(game, corner, Some(Knight { owner: player, level: KnightLevel::Basic, active: false }));
+    Ok(())
+}
+
+pub fn activate_knight(game: &mut Game, player: PlayerID, corner: &hex_coordinates::Corner) -> Result<(), String> {
+    settings(game)?;
+    let knight = knight_at(game, corner, player)?;
+    if knight.active {
+        return Err("That knight is already active".to_string());
+    }
+    let price = game.rules.cost(Purchase::ActivateKnight).clone();
+    pay(game, player, &price)?;
+    set_knight(game, corner, Some(Knight { active: true, ..knight }));
+    Ok(())
+}
+
+/// Promote a knight one level. Only players with politics at level 3 or more can have mighty
+/// knights.
+pub fn promote_knight(game: &mut Game, player: PlayerID, corner: &hex_coordinates::Corner) -> Result<(), String> {
+    let knight = knight_at(game, corner, player)?;
+    let level = match knight.level {
+        KnightLevel::Basic => KnightLevel::Strong,
+        KnightLevel::Strong => KnightLevel::Mighty,
+        KnightLevel::Mighty => return Err("That knight is already mighty".to_string()),
+    };
+    if level == KnightLevel::Mighty && state(game)?.improvements(player).politics < MIGHTY_KNIGHT_LEVEL {
+        return Err(format!("Mighty knights need politics at level {}", MIGHTY_KNIGHT_LEVEL));
+    }
+    if !knights_available(game, player, level)? {
+        return Err(format!("There are no {:?} knights left", level));
+    }
+    let price = game.rules.cost(Purchase::PromoteKnight).clone();
+    pay(game, player, &price)?;
+    set_knight(game, corner, Some(Knight { level, ..knight }));
+    Ok(())
+}
+
+/// Move an active knight along `player`'s roads to an empty corner. Moving deactivates it.
+pub fn move_knight(
+    game: &mut Game,
+    player: PlayerID,
+    from: &hex_coordinates::Corner,
+    to: &hex_coordinates::Corner
+) -> Result<(), String> {
+    settings(game)?;
+    let knight = knight_at(game, from, player)?;
+    if !knight.active {
+        return Err("Only active knights can move".to_string());
+    }
+    if !is_empty(&game.grid, to) || !reachable(&game.grid, from, player).contains(to) {
+        return Err(format!("That knight can't move to {}", to));
+    }
+    set_knight(game, from, None);
+    set_knight(game, to, Some(Knight { active: false, ..knight }));
+    Ok(())
+}
+
+/// Move an active knight onto a corner with another player's weaker knight, which deactivates
+/// it. The other player's knight has to retreat along their own roads to `retreat`, an empty
+/// corner. If it has nowhere to go, it is removed, and `retreat` must be `None`.
+pub fn displace_knight(
+    game: &mut Game,
+    player: PlayerID,
+    from: &hex_coordinates::Corner,
+    to: &hex_coordinates::Corner,
+    retreat: Option<&hex_coordinates::Corner>
+) -> Result<(), String> {
+    settings(game)?;
+    let knight = knight_at(game, from, player)?;
+    if !knight.active {
+        return Err("Only active knights can displace other knights".to_string());
+    }
+    let victim = match game.grid.corners.get(to).and_then(|c| c.knight) {
+        Some(victim) if victim.owner != player && victim.level < knight.level => victim,
+        _ => return Err(format!("There is no weaker knight to displace at {}", to)),
+    };
+    // The attacker has to be able to reach the victim's corner, but the victim's own knight
+    // doesn't block the way there.
+    set_knight(game, to, None);
+    let can_reach = reachable(&game.grid, from, player).contains(to);
+    set_knight(game, to, Some(victim));
+    if !can_reach {
+        return Err(format!("That knight can't reach {}", to));
+    }
+
+    let retreats: HashSet<_> = reachable(&game.grid, to, victim.owner).into_iter()
+        .filter(|c| c != from && is_empty(&game.grid, c))
+        .collect();
+    match retreat {
+        Some(r) if !retreats.contains(r) => return Err(format!("The displaced knight can't retreat to {}", r)),
+        None if !retreats.is_empty() => return Err("The displaced knight has somewhere to retreat to".to_string()),
+        _ => {},
+    }
+
+    set_knight(game, from, None);
+    set_knight(game, to, Some(Knight { active: false, ..knight }));
+    if let Some(r) = retreat {
+        set_knight(game, r, Some(victim));
+    }
+    Ok(())
+}
+
+/// Start Cities & Knights for a new game, if the rules use it.
+pub(crate) fn new_state<R: Rng>(rules: &Rules, rng: &mut R) -> Option<State> {
+    rules.cities_and_knights.as_ref().map(|_| State::new(rng))
+}
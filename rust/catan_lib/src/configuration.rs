@@ -1,12 +1,23 @@
+use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use serde_json;
 use hexgrid::hex_coordinates;
+use super::generation::constraints::GenerationConstraint;
+use super::types::{TileType, Resource, Port, Purchase};
+use super::player::Resources;
+use super::dice::DiceMode;
+use super::scenarios::ScenarioRules;
+use shape::BoardShape;
+
+pub mod shape;
+pub mod registry;
 
 /// The rules of the game should remain immutable for the entire game.
 ///
 /// This struct includes various `defaults_` functions. These construct `Rules` objects with the
 /// right values for the official rules for various versions of the game. To configure these rules,
-/// see the files in the `configuration_defaults` folder.
+/// see the files in the `configuration_defaults` folder. To pick from these by name, or to load
+/// variants from files, see `registry::Registry`.
 ///
 /// These `defaults_` functions include calls to `unwrap()`, which can panic. However, this
 /// `unwrap()` will only panic if the JSON configuration file is improperly formatted. The contents
@@ -27,6 +38,69 @@ pub struct Rules {
     pub devcard_monopoly_count: u32,
     pub devcard_road_count: u32,
     pub special_build_phase: bool,
+    /// Points needed to win.
+    #[serde(default = "default_victory_points")]
+    pub victory_points: u32,
+    /// What everything costs. Defaults to the prices in the official rules.
+    #[serde(default)]
+    pub costs: Costs,
+    /// How the dice are rolled. Defaults to `DiceMode::Standard`.
+    #[serde(default)]
+    pub dice: DiceMode,
+    /// Settings for the Cities & Knights expansion, or `None` to play without it.
+    #[serde(default)]
+    pub cities_and_knights: Option<CitiesAndKnightsRules>,
+    /// The scenario to play, or `None` to play without one. See the `scenarios` module.
+    #[serde(default)]
+    pub scenario: Option<ScenarioRules>,
+}
+
+/// Settings for the Cities & Knights expansion. See the `cities_and_knights` module.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CitiesAndKnightsRules {
+    /// How many times the barbarian ship moves before it attacks.
+    pub barbarian_track_length: u32,
+    /// The most progress cards a player can hold, not counting ones worth victory points.
+    pub progress_card_limit: u32,
+    pub basic_knight_count: u32,
+    pub strong_knight_count: u32,
+    pub mighty_knight_count: u32,
+}
+
+fn default_victory_points() -> u32 {
+    10
+}
+
+/// The price of each `Purchase`. Any price that isn't given is the one from the official rules.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Costs {
+    pub road: Resources,
+    pub ship: Resources,
+    pub settlement: Resources,
+    pub city: Resources,
+    pub development_card: Resources,
+    pub knight: Resources,
+    pub activate_knight: Resources,
+    pub promote_knight: Resources,
+}
+
+impl Default for Costs {
+    /// The prices in the official rules.
+    fn default() -> Costs {
+        let recipe = |items: &[(Resource, u32)]| items.iter()
+            .fold(Resources::new(), |total, (resource, count)| total + &Resources::of(*resource, *count));
+        Costs {
+            road: recipe(&[(Resource::Wood, 1), (Resource::Clay, 1)]),
+            ship: recipe(&[(Resource::Wood, 1), (Resource::Sheep, 1)]),
+            settlement: recipe(&[(Resource::Wood, 1), (Resource::Clay, 1), (Resource::Wheat, 1), (Resource::Sheep, 1)]),
+            city: recipe(&[(Resource::Wheat, 2), (Resource::Stone, 3)]),
+            development_card: recipe(&[(Resource::Wheat, 1), (Resource::Sheep, 1), (Resource::Stone, 1)]),
+            knight: recipe(&[(Resource::Sheep, 1), (Resource::Stone, 1)]),
+            activate_knight: recipe(&[(Resource::Wheat, 1)]),
+            promote_knight: recipe(&[(Resource::Sheep, 1), (Resource::Stone, 1)]),
+        }
+    }
 }
 
 impl Rules {
@@ -60,6 +134,26 @@ impl Rules {
         serde_json::from_str(config_str).unwrap()
     }
 
+    /// Get the default rules for Cities & Knights
+    ///
+    /// ```
+    /// let rules = catan_lib::configuration::Rules::defaults_cities_and_knights();
+    /// ```
+    pub fn defaults_cities_and_knights() -> Rules {
+        let config_str = include_str!("configuration_defaults/cities_and_knights/rules.json");
+        serde_json::from_str(config_str).unwrap()
+    }
+
+    /// Get the default rules for the Fishermen of Catan scenario
+    ///
+    /// ```
+    /// let rules = catan_lib::configuration::Rules::defaults_fishermen();
+    /// ```
+    pub fn defaults_fishermen() -> Rules {
+        let config_str = include_str!("configuration_defaults/fishermen/rules.json");
+        serde_json::from_str(config_str).unwrap()
+    }
+
     /// Get the default rules for the base game
     ///
     /// ```
@@ -69,7 +163,36 @@ impl Rules {
         let config_str = include_str!("configuration_defaults/sheepland/rules.json");
         serde_json::from_str(config_str).unwrap()
     }
-    // TODO: Add the rest of the possible defaults
+
+    pub fn cost(&self, purchase: Purchase) -> &Resources {
+        match purchase {
+            Purchase::Road => &self.costs.road,
+            Purchase::Ship => &self.costs.ship,
+            Purchase::Settlement => &self.costs.settlement,
+            Purchase::City => &self.costs.city,
+            Purchase::DevelopmentCard => &self.costs.development_card,
+            Purchase::Knight => &self.costs.knight,
+            Purchase::ActivateKnight => &self.costs.activate_knight,
+            Purchase::PromoteKnight => &self.costs.promote_knight,
+        }
+    }
+
+    /// True if `resources` are enough to pay for `purchase`. This only checks the price, not
+    /// whether there is a piece left or a place to build it.
+    ///
+    /// ```
+    /// use catan_lib::configuration::Rules;
+    /// use catan_lib::player::Resources;
+    /// use catan_lib::types::{Purchase, Resource};
+    ///
+    /// let rules = Rules::defaults_vanilla();
+    /// let hand = Resources::of(Resource::Wood, 1) + &Resources::of(Resource::Clay, 1);
+    /// assert!(rules.can_afford(&hand, Purchase::Road));
+    /// assert!(!rules.can_afford(&hand, Purchase::Settlement));
+    /// ```
+    pub fn can_afford(&self, resources: &Resources, purchase: Purchase) -> bool {
+        resources.contains(self.cost(purchase))
+    }
 }
 
 
@@ -87,8 +210,8 @@ pub struct MapGenerationSettings {
     pub desert_count: u32,
     pub gold_count: u32,
     pub ocean_count: u32,
-    // If these constraints are too strict, there is no possible solution, and it will run for a very
-    // long time trying to find one.
+    // If these constraints are too strict, there is no possible solution, and generation fails with
+    // `GenerationError::Unsatisfiable`. Proving that can take a long time, though.
     // A corner score is the sum of the score of each number on the three adjacent tiles, where the "score" is the
     // number of dots on the Catan piece, representing the probability of that number being rolled.
     // These constraints only apply to corners with three adjacent tiles that aren't deserts or oceans.
@@ -99,11 +222,257 @@ pub struct MapGenerationSettings {
     /// If true, then prevent generating two adjacent tiles of the same type (except for oceans)
     pub avoid_adjacent: bool,
     /// List of all valid tile coordinates in the game.
+    #[serde(default)]
     pub coords: Vec<hex_coordinates::Tile>,
+    /// The shape of the board, instead of (or as well as) listing every tile in `coords`. See
+    /// `resolve_shape`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<BoardShape>,
     pub numbers: Vec<i32>,
+    /// How `numbers` are put on the tiles. Defaults to `NumberPlacement::Random`.
+    #[serde(default)]
+    pub number_placement: NumberPlacement,
+    /// Extra rules for generation, on top of the ones from the fields above.
+    #[serde(default)]
+    pub constraints: Vec<GenerationConstraint>,
+    /// Tiles that are always the same, for scenario maps. Only the rest of `coords` is randomly
+    /// generated. See `PinnedTile`.
+    #[serde(default)]
+    pub pinned_tiles: Vec<PinnedTile>,
+    /// Ports that are always placed.
+    #[serde(default)]
+    pub pinned_ports: Vec<PinnedPort>,
+}
+
+/// The order of the number tokens in the official rules, from A to R.
+pub const ALPHABETICAL_NUMBERS: [i32; 18] = [5, 2, 6, 3, 8, 10, 9, 12, 11, 4, 8, 10, 9, 4, 5, 6, 3, 11];
+
+/// The ways that numbers can be put on the tiles.
+///
+/// Only `Random` follows the constraints on numbers. The other modes put the numbers exactly where
+/// they say, and generation fails with `GenerationError::Unsatisfiable` if they don't fit the
+/// board. Numbers in `PinnedTile`s are always kept, and are skipped over by the other modes.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum NumberPlacement {
+    /// Shuffle `numbers` onto the tiles, following the constraints.
+    #[default]
+    Random,
+    /// Lay the numbers along a spiral, like in the official rules: start at a corner, go
+    /// counterclockwise around the edge of the board, and keep going around on the inside. Tiles
+    /// without a number, like the desert, are skipped.
+    ///
+    /// The spiral starts at `start`, or a random corner if that isn't given. The numbers are laid
+    /// in the order of `sequence`, which defaults to `ALPHABETICAL_NUMBERS`, instead of using
+    /// `numbers`.
+    Spiral {
+        #[serde(default)]
+        start: Option<hex_coordinates::Tile>,
+        #[serde(default = "alphabetical_numbers")]
+        sequence: Vec<i32>,
+    },
+    /// Put these numbers on these tiles. Every tile that gets a number has to be listed, so this
+    /// is meant for maps where the tiles are pinned too.
+    Fixed { numbers: HashMap<hex_coordinates::Tile, i32> },
+}
+
+fn alphabetical_numbers() -> Vec<i32> {
+    ALPHABETICAL_NUMBERS.to_vec()
+}
+
+/// A tile that is placed as given, instead of being randomly generated.
+///
+/// The tile counts and `numbers` in `MapGenerationSettings` are for the whole board, so a pinned
+/// tile uses up one of its type, and a pinned number uses up one of that number.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PinnedTile {
+    pub coord: hex_coordinates::Tile,
+    pub tile_type: TileType,
+    /// The number on the tile. If this is `None` on a resource tile, a random number is still
+    /// given to it.
+    #[serde(default)]
+    pub number: Option<i32>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PinnedPort {
+    pub edge: hex_coordinates::Edge,
+    pub port: Port,
 }
 
 impl MapGenerationSettings {
+    /// The pinned tile at these coordinates, if there is one.
+    pub fn pinned_tile(&self, coord: &hex_coordinates::Tile) -> Option<&PinnedTile> {
+        self.pinned_tiles.iter().find(|p| p.coord == *coord)
+    }
+
+    /// Load settings from JSON, with `shape` already resolved into `coords`.
+    pub fn from_json(json: &str) -> Result<MapGenerationSettings, String> {
+        let mut settings: MapGenerationSettings = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid map generation settings: {}", e))?;
+        settings.resolve_shape()?;
+        Ok(settings)
+    }
+
+    /// Add every tile in `shape` to `coords`, and then clear `shape`. Generation only looks at
+    /// `coords`, so this has to be called on settings that were deserialized some other way than
+    /// `from_json`.
+    pub fn resolve_shape(&mut self) -> Result<(), String> {
+        if let Some(shape) = self.shape.take() {
+            for coord in shape.resolve()? {
+                if !self.coords.contains(&coord) {
+                    self.coords.push(coord);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// How many tiles of this type the board has.
+    pub fn tile_count(&self, tile_type: TileType) -> u32 {
+        match tile_type {
+            TileType::Resource(Resource::Wheat) => self.wheat_count,
+            TileType::Resource(Resource::Wood) => self.wood_count,
+            TileType::Resource(Resource::Clay) => self.clay_count,
+            TileType::Resource(Resource::Stone) => self.stone_count,
+            TileType::Resource(Resource::Sheep) => self.sheep_count,
+            TileType::Resource(Resource::Gold) => self.gold_count,
+            TileType::Desert => self.desert_count,
+            TileType::Ocean => self.ocean_count,
+        }
+    }
+
+    /// Check that these settings make sense, before trying to generate a map with them. Returns
+    /// every problem found, as messages that can be shown to the user. The generators call this
+    /// first, and fail with `GenerationError::InvalidSettings` if there are any problems.
+    ///
+    /// This doesn't check whether the constraints can all be met at once. That is only found out
+    /// by trying.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        for (i, coord) in self.coords.iter().enumerate() {
+            if self.coords[..i].contains(coord) {
+                problems.push(format!("Tile {} is listed more than once", coord));
+            }
+        }
+
+        let resource_tiles = self.wheat_count + self.wood_count + self.clay_count + self.stone_count +
+            self.sheep_count + self.gold_count;
+        let tiles = resource_tiles + self.desert_count + self.ocean_count;
+        if tiles as usize != self.coords.len() {
+            problems.push(format!("There are {} tiles, but {} places to put them", tiles, self.coords.len()));
+        }
+
+        if self.min_corner_score > self.max_corner_score {
+            problems.push(format!(
+                "The minimum corner score ({}) is higher than the maximum ({})",
+                self.min_corner_score, self.max_corner_score
+            ));
+        }
+
+        let mut numbers: Vec<i32> = self.numbers.clone();
+        match &self.number_placement {
+            NumberPlacement::Random => {
+                if self.numbers.len() != resource_tiles as usize {
+                    problems.push(format!(
+                        "There are {} numbers, but {} resource tiles",
+                        self.numbers.len(), resource_tiles
+                    ));
+                }
+            },
+            NumberPlacement::Spiral { start, sequence } => {
+                if sequence.len() < resource_tiles as usize {
+                    problems.push(format!(
+                        "The spiral has {} numbers, but there are {} resource tiles",
+                        sequence.len(), resource_tiles
+                    ));
+                }
+                if let Some(start) = start.filter(|s| !self.coords.contains(s)) {
+                    problems.push(format!("The spiral starts at {}, which isn't on the board", start));
+                }
+                numbers.extend(sequence.iter().cloned());
+            },
+            NumberPlacement::Fixed { numbers: layout } => {
+                for (coord, number) in layout.iter() {
+                    if !self.coords.contains(coord) {
+                        problems.push(format!("Number {} is on {}, which isn't on the board", number, coord));
+                    }
+                    numbers.push(*number);
+                }
+            },
+        }
+        numbers.extend(self.pinned_tiles.iter().filter_map(|p| p.number));
+        numbers.sort();
+        numbers.dedup();
+        for number in numbers.into_iter().filter(|n| !(2..=12).contains(n) || *n == 7) {
+            problems.push(format!("{} can't be rolled, so it can't be a number on a tile", number));
+        }
+
+        for (i, pinned) in self.pinned_tiles.iter().enumerate() {
+            if !self.coords.contains(&pinned.coord) {
+                problems.push(format!("Pinned tile {} isn't on the board", pinned.coord));
+            }
+            if self.pinned_tiles[..i].iter().any(|p| p.coord == pinned.coord) {
+                problems.push(format!("Tile {} is pinned more than once", pinned.coord));
+            }
+            if pinned.number.is_some() && !matches!(pinned.tile_type, TileType::Resource(_)) {
+                problems.push(format!("Pinned tile {} has a number, but isn't a resource tile", pinned.coord));
+            }
+        }
+        if self.number_placement == NumberPlacement::Random {
+            let mut pinned_numbers: Vec<i32> = self.pinned_tiles.iter().filter_map(|p| p.number).collect();
+            pinned_numbers.sort();
+            pinned_numbers.dedup();
+            for number in pinned_numbers {
+                let pinned = self.pinned_tiles.iter().filter(|p| p.number == Some(number)).count();
+                let available = self.numbers.iter().filter(|n| **n == number).count();
+                if pinned > available {
+                    problems.push(format!(
+                        "{} tiles have {} pinned on them, but there are only {} of that number",
+                        pinned, number, available
+                    ));
+                }
+            }
+        }
+
+        let mut pinned_types: Vec<TileType> = Vec::new();
+        for pinned in self.pinned_tiles.iter() {
+            if !pinned_types.contains(&pinned.tile_type) {
+                pinned_types.push(pinned.tile_type);
+            }
+        }
+        for tile_type in pinned_types {
+            let pinned = self.pinned_tiles.iter().filter(|p| p.tile_type == tile_type).count();
+            if pinned > self.tile_count(tile_type) as usize {
+                problems.push(format!(
+                    "{} tiles of type {:?} are pinned, but there are only {}",
+                    pinned, tile_type, self.tile_count(tile_type)
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Every constraint that generation has to follow, including the ones implied by
+    /// `avoid_adjacent` and the corner scores. Oceans always have to be connected.
+    pub fn all_constraints(&self) -> Vec<GenerationConstraint> {
+        let mut constraints = vec![
+            GenerationConstraint::ContiguousOcean,
+            GenerationConstraint::CornerScore { min: self.min_corner_score, max: self.max_corner_score },
+        ];
+        if self.avoid_adjacent {
+            constraints.push(GenerationConstraint::AvoidAdjacent);
+        }
+        constraints.extend(self.constraints.iter().cloned());
+        constraints
+    }
+
     /// Default map generation for base game
     ///
     /// ```
@@ -111,7 +480,7 @@ impl MapGenerationSettings {
     /// ```
     pub fn defaults_vanilla() -> MapGenerationSettings {
         let config_str = include_str!("configuration_defaults/vanilla/generation.json");
-        serde_json::from_str(config_str).unwrap()
+        MapGenerationSettings::from_json(config_str).unwrap()
     }
 
     /// Default map generation for base game
@@ -121,7 +490,7 @@ impl MapGenerationSettings {
     /// ```
     pub fn defaults_vanilla56() -> MapGenerationSettings {
         let config_str = include_str!("configuration_defaults/vanilla56/generation.json");
-        serde_json::from_str(config_str).unwrap()
+        MapGenerationSettings::from_json(config_str).unwrap()
     }
 
     /// Default map generation for base game
@@ -131,7 +500,7 @@ impl MapGenerationSettings {
     /// ```
     pub fn defaults_seafarers() -> MapGenerationSettings {
         let config_str = include_str!("configuration_defaults/seafarers/generation.json");
-        serde_json::from_str(config_str).unwrap()
+        MapGenerationSettings::from_json(config_str).unwrap()
     }
 
     /// Default map generation for base game
@@ -141,6 +510,6 @@ impl MapGenerationSettings {
     /// ```
     pub fn defaults_sheepland() -> MapGenerationSettings {
         let config_str = include_str!("configuration_defaults/sheepland/generation.json");
-        serde_json::from_str(config_str).unwrap()
+        MapGenerationSettings::from_json(config_str).unwrap()
     }
 }
\ No newline at end of file
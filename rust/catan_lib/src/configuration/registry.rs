@@ -0,0 +1,262 @@
+//! Named sets of rules and map settings, both built in and loaded from files, for a setup screen
+//! to choose from.
+//!
+//! A variant file is JSON like this:
+//!
+//! ```json
+//! {
+//!   "name": "seafarers-12",
+//!   "description": "Seafarers, played to 12 points",
+//!   "extends": "seafarers",
+//!   "rules": { "victory_points": 12 }
+//! }
+//! ```
+//!
+//! or the same thing in TOML:
+//!
+//! ```toml
+//! name = "seafarers-12"
+//! description = "Seafarers, played to 12 points"
+//! extends = "seafarers"
+//!
+//! [rules]
+//! victory_points = 12
+//! ```
+//!
+//! A variant that `extends` another one starts with all of its rules and map settings, and only
+//! needs to give the ones it changes. Objects are merged field by field, and anything else
+//! (including lists) replaces what was there, except for `null`, which leaves it as it was. A
+//! variant that doesn't extend anything has to give all of `rules` and `map`, in the same format
+//! as the files in `configuration_defaults`.
+
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use serde_json::{self, Value};
+use super::{Rules, MapGenerationSettings};
+
+const BUILTIN: [(&str, &str, &str, &str); 6] = [
+    (
+        "vanilla",
+        "The base game, for 3 or 4 players",
+        include_str!("../configuration_defaults/vanilla/rules.json"),
+        include_str!("../configuration_defaults/vanilla/generation.json"),
+    ),
+    (
+        "vanilla56",
+        "The base game with the 5-6 player extension",
+        include_str!("../configuration_defaults/vanilla56/rules.json"),
+        include_str!("../configuration_defaults/vanilla56/generation.json"),
+    ),
+    (
+        "seafarers",
+        "Seafarers, with islands, ships and gold",
+        include_str!("../configuration_defaults/seafarers/rules.json"),
+        include_str!("../configuration_defaults/seafarers/generation.json"),
+    ),
+    (
+        "cities_and_knights",
+        "Cities & Knights, with commodities, knights and barbarians",
+        include_str!("../configuration_defaults/cities_and_knights/rules.json"),
+        include_str!("../configuration_defaults/cities_and_knights/generation.json"),
+    ),
+    (
+        "fishermen",
+        "The Fishermen of Catan scenario, with fish shoals along the coast",
+        include_str!("../configuration_defaults/fishermen/rules.json"),
+        include_str!("../configuration_defaults/fishermen/generation.json"),
+    ),
+    (
+        "sheepland",
+        "Nearly every tile is sheep",
+        include_str!("../configuration_defaults/sheepland/rules.json"),
+        include_str!("../configuration_defaults/sheepland/generation.json"),
+    ),
+];
+
+/// A variant as it is written in a file, before inheritance is applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct VariantFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    rules: Value,
+    #[serde(default)]
+    map: Value,
+}
+
+/// A summary of a variant, for listing them.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VariantInfo {
+    pub name: String,
+    pub description: String,
+    /// The variant that this one is based on.
+    pub extends: Option<String>,
+    /// True for the variants that come with the library, rather than being loaded from a file.
+    pub builtin: bool,
+}
+
+/// A variant with inheritance applied, ready to start a game with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub description: String,
+    pub rules: Rules,
+    pub map: MapGenerationSettings,
+}
+
+/// Every variant that can be picked. See the module documentation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registry {
+    variants: Vec<(VariantFile, bool)>,
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+impl Registry {
+    /// A registry with the built-in variants: `vanilla`, `vanilla56`, `seafarers`,
+    /// `cities_and_knights`, `fishermen` and `sheepland`.
+    ///
+    /// ```
+    /// let registry = catan_lib::configuration::registry::Registry::new();
+    /// let seafarers = registry.get("seafarers").unwrap();
+    /// assert!(seafarers.rules.can_build_ships);
+    /// ```
+    pub fn new() -> Registry {
+        let variants = BUILTIN.iter()
+            .map(|(name, description, rules, map)| (VariantFile {
+                name: name.to_string(),
+                description: description.to_string(),
+                extends: None,
+                rules: serde_json::from_str(rules).unwrap(),
+                map: serde_json::from_str(map).unwrap(),
+            }, true))
+            .collect();
+        Registry { variants }
+    }
+
+    /// Add a variant from the contents of a variant file. Fails if the JSON is invalid, or if
+    /// there is already a variant with the same name. Inheritance isn't checked until `get`, so
+    /// variants can be added in any order.
+    pub fn add_json(&mut self, json: &str) -> Result<(), String> {
+        let variant: VariantFile = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid variant: {}", e))?;
+        self.add(variant)
+    }
+
+    /// Add a variant from the contents of a variant file written in TOML, in the same way as
+    /// `add_json`. TOML has no `null`, so the only way to leave a setting as it was is to leave
+    /// it out.
+    pub fn add_toml(&mut self, toml: &str) -> Result<(), String> {
+        let variant: VariantFile = toml::from_str(toml)
+            .map_err(|e| format!("Invalid variant: {}", e))?;
+        self.add(variant)
+    }
+
+    fn add(&mut self, variant: VariantFile) -> Result<(), String> {
+        if self.find(&variant.name).is_some() {
+            return Err(format!("There is already a variant called {}", variant.name));
+        }
+        self.variants.push((variant, false));
+        Ok(())
+    }
+
+    /// Add a variant from a file. Files ending in `.toml` are read as TOML, and everything else as
+    /// JSON.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+        let added = if path.extension().map_or(false, |e| e == "toml") {
+            self.add_toml(&contents)
+        } else {
+            self.add_json(&contents)
+        };
+        added.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Add every `.json` and `.toml` file in a folder, in order of file name. Stops at the first
+    /// file that fails to load.
+    pub fn load_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), String> {
+        let dir = dir.as_ref();
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .map_err(|e| format!("Couldn't read {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |e| e == "json" || e == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            self.load_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Every variant, with the built-in ones first and the rest in the order they were added.
+    pub fn list(&self) -> Vec<VariantInfo> {
+        self.variants.iter()
+            .map(|(v, builtin)| VariantInfo {
+                name: v.name.clone(),
+                description: v.description.clone(),
+                extends: v.extends.clone(),
+                builtin: *builtin,
+            })
+            .collect()
+    }
+
+    /// Look up a variant by name, and apply inheritance. Fails if the variant, or one that it
+    /// extends, doesn't exist or has invalid settings, or if variants extend each other in a loop.
+    pub fn get(&self, name: &str) -> Result<Variant, String> {
+        let variant = self.find(name).ok_or_else(|| format!("There is no variant called {}", name))?;
+
+        // Collect the chain of variants from this one back to one that doesn't extend anything.
+        let mut chain = vec![variant];
+        while let Some(parent) = &chain.last().unwrap().extends {
+            if chain.iter().any(|v| v.name == *parent) {
+                return Err(format!("Variant {} extends itself", parent));
+            }
+            let parent = self.find(parent)
+                .ok_or_else(|| format!("Variant {} extends {}, which doesn't exist", chain.last().unwrap().name, parent))?;
+            chain.push(parent);
+        }
+
+        let mut rules = Value::Null;
+        let mut map = Value::Null;
+        for v in chain.iter().rev() {
+            merge(&mut rules, &v.rules);
+            merge(&mut map, &v.map);
+        }
+
+        let rules: Rules = serde_json::from_value(rules)
+            .map_err(|e| format!("Invalid rules in variant {}: {}", name, e))?;
+        let mut map: MapGenerationSettings = serde_json::from_value(map)
+            .map_err(|e| format!("Invalid map settings in variant {}: {}", name, e))?;
+        map.resolve_shape()?;
+
+        Ok(Variant { name: variant.name.clone(), description: variant.description.clone(), rules, map })
+    }
+
+    fn find(&self, name: &str) -> Option<&VariantFile> {
+        self.variants.iter().map(|(v, _)| v).find(|v| v.name == name)
+    }
+}
+
+/// Apply `overrides` on top of `base`. Objects are merged field by field, and everything else is
+/// replaced.
+fn merge(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge(base.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        },
+        (_, Value::Null) => {},
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
@@ -0,0 +1,102 @@
+//! Describe the tiles of a board by its shape, instead of listing every coordinate.
+
+use std::collections::BTreeSet;
+use serde::{Serialize, Deserialize};
+use hexgrid::hex_coordinates;
+
+/// The shape of a board. Shapes are turned into a list of coordinates by `resolve`.
+///
+/// In JSON, a shape is an object with a `type` field, for example
+/// `{"type": "Hexagon", "center": "2,2,Tile", "radius": 2}` for the base game.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BoardShape {
+    /// Every tile at most `radius` steps away from `center`. A radius of 0 is a single tile.
+    Hexagon { center: hex_coordinates::Tile, radius: u32 },
+    /// `height` rows of `width` tiles each. `corner` is the first tile of the bottom row. Each row
+    /// is shifted back every other row, so that the board is a rectangle instead of leaning to
+    /// the east.
+    Rectangle { corner: hex_coordinates::Tile, width: u32, height: u32 },
+    /// A picture of the board, with one string per row from north to south. Any character other
+    /// than a space or a `.` is a tile. Each tile is two characters wide, and each row is shifted
+    /// one character to the right of the row below it, like this:
+    ///
+    /// ```text
+    ///   x x x
+    ///  x x x x
+    /// x x x x x
+    ///  x x x x
+    ///   x x x
+    /// ```
+    ///
+    /// `origin` is the tile that would be at the first character of the bottom row.
+    Mask { origin: hex_coordinates::Tile, rows: Vec<String> },
+    /// Every tile that is in any of these shapes.
+    Union { shapes: Vec<BoardShape> },
+    /// Every tile in `shape` that isn't in any of the `minus` shapes.
+    Difference { shape: Box<BoardShape>, minus: Vec<BoardShape> },
+}
+
+impl BoardShape {
+    /// List every tile in the shape, sorted from south to north and then west to east.
+    ///
+    /// Fails if a `Mask` has a tile between two columns.
+    pub fn resolve(&self) -> Result<Vec<hex_coordinates::Tile>, String> {
+        Ok(self.tiles()?.into_iter().map(|(y, x)| hex_coordinates::Tile::new(x, y)).collect())
+    }
+
+    /// Tiles as `(y, x)`, so that the set is in the same order as `resolve` returns them.
+    fn tiles(&self) -> Result<BTreeSet<(i32, i32)>, String> {
+        let mut tiles = BTreeSet::new();
+        match self {
+            BoardShape::Hexagon { center, radius } => {
+                let r = *radius as i32;
+                for dy in -r..=r {
+                    for dx in (-r).max(-r - dy)..=r.min(r - dy) {
+                        tiles.insert((center.y + dy, center.x + dx));
+                    }
+                }
+            },
+            BoardShape::Rectangle { corner, width, height } => {
+                for row in 0..*height as i32 {
+                    let start = corner.x - row / 2;
+                    for x in start..start + *width as i32 {
+                        tiles.insert((corner.y + row, x));
+                    }
+                }
+            },
+            BoardShape::Mask { origin, rows } => {
+                for (i, line) in rows.iter().enumerate() {
+                    let row = (rows.len() - 1 - i) as i32;
+                    for (column, c) in line.chars().enumerate() {
+                        if c == ' ' || c == '.' {
+                            continue;
+                        }
+                        let offset = column as i32 - row;
+                        if offset % 2 != 0 {
+                            return Err(format!(
+                                "Board mask row {} has a tile at column {}, which is between two tiles",
+                                i + 1, column + 1
+                            ));
+                        }
+                        tiles.insert((origin.y + row, origin.x + offset / 2));
+                    }
+                }
+            },
+            BoardShape::Union { shapes } => {
+                for shape in shapes {
+                    tiles.extend(shape.tiles()?);
+                }
+            },
+            BoardShape::Difference { shape, minus } => {
+                tiles = shape.tiles()?;
+                for shape in minus {
+                    for tile in shape.tiles()? {
+                        tiles.remove(&tile);
+                    }
+                }
+            },
+        }
+        Ok(tiles)
+    }
+}
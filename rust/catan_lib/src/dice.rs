@@ -0,0 +1,83 @@
+//! Rolling the dice. `Rules::dice` picks how: with real dice, or in one of the ways that makes
+//! long streaks of the same numbers less likely.
+
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+/// How the dice are rolled.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DiceMode {
+    /// Two ordinary six-sided dice.
+    #[default]
+    Standard,
+    /// A deck of 36 cards, one for each way two dice can land, drawn without replacement. Once
+    /// only `reshuffle_at` cards are left, all 36 are shuffled back together. Over a whole deck,
+    /// every number comes up exactly as often as it should.
+    Deck { reshuffle_at: u32 },
+    /// Like two ordinary dice, but a total is less likely the more often it came up in the last
+    /// `memory` rolls. Each of the 36 ways the dice can land has a weight of `memory + 1`, minus
+    /// how many of the last `memory` rolls had the same total. With a `memory` of 0, this rolls
+    /// just like `Standard`.
+    Balanced { memory: u32 },
+}
+
+/// What the dice remember between rolls. Only `Deck` and `Balanced` need to remember anything.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Dice {
+    /// The cards left in the deck, for `DiceMode::Deck`.
+    deck: Vec<[u32; 2]>,
+    /// The totals of the most recent rolls, oldest first, for `DiceMode::Balanced`.
+    recent: Vec<u32>,
+}
+
+/// Every way two dice can land.
+fn all_rolls() -> Vec<[u32; 2]> {
+    (1..=6).flat_map(|a| (1..=6).map(move |b| [a, b])).collect()
+}
+
+impl Dice {
+    pub fn new() -> Dice {
+        Dice::default()
+    }
+
+    /// Roll both dice.
+    pub fn roll<R: Rng>(&mut self, mode: &DiceMode, rng: &mut R) -> [u32; 2] {
+        match *mode {
+            DiceMode::Standard => [rng.gen_range(1, 7), rng.gen_range(1, 7)],
+            DiceMode::Deck { reshuffle_at } => {
+                if self.deck.len() <= reshuffle_at as usize {
+                    self.deck = all_rolls();
+                }
+                let card = rng.gen_range(0, self.deck.len());
+                self.deck.swap_remove(card)
+            },
+            DiceMode::Balanced { memory } => {
+                // The memory can be smaller than it was on the last roll, if the rules changed
+                // since then, so forget anything older than it first.
+                let extra = self.recent.len().saturating_sub(memory as usize);
+                self.recent.drain(..extra);
+
+                let rolls = all_rolls();
+                let weight = |roll: &[u32; 2]| {
+                    let seen = self.recent.iter().filter(|total| **total == roll[0] + roll[1]).count() as u32;
+                    (memory + 1).saturating_sub(seen)
+                };
+                let mut pick = rng.gen_range(0, rolls.iter().map(weight).sum::<u32>());
+                let mut result = rolls[0];
+                for roll in rolls.iter() {
+                    if pick < weight(roll) {
+                        result = *roll;
+                        break;
+                    }
+                    pick -= weight(roll);
+                }
+
+                self.recent.push(result[0] + result[1]);
+                let extra = self.recent.len().saturating_sub(memory as usize);
+                self.recent.drain(..extra);
+                result
+            },
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! A record of everything that has happened in a game, in order, for replays and for showing
+//! players what just happened.
+
+use serde::{Serialize, Deserialize};
+use super::player::PlayerID;
+use super::cities_and_knights::{Attack, EventDieFace, Track};
+use super::scenarios::fishermen::FishExchange;
+use super::types::FishToken;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    /// A player rolled the dice, and got these two numbers.
+    Roll { player: PlayerID, dice: [u32; 2] },
+    /// In Cities & Knights, a player rolled the event die along with the dice.
+    EventDie { player: PlayerID, face: EventDieFace },
+    /// In Cities & Knights, the barbarians arrived and fought the knights.
+    BarbarianAttack(Attack),
+    /// In Cities & Knights, a player drew a progress card. Only the track is recorded, so that the
+    /// log doesn't show other players what the card was.
+    ProgressCardDrawn { player: PlayerID, track: Track },
+    /// In Fishermen of Catan, a player drew a token from the bag.
+    FishCaught { player: PlayerID, token: FishToken },
+    /// In Fishermen of Catan, a player spent fish at the fish exchange.
+    FishExchanged { player: PlayerID, exchange: FishExchange },
+}
@@ -1,13 +1,111 @@
-use hexgrid::{hex_coordinates};
-use super::types::{Tile, TileType, Resource};
+use std::cell::Cell;
+use std::fmt;
+use hexgrid::{hex_coordinates, GridMut};
+use super::types::{Tile, TileType, Resource, Edge, Road};
 use super::configuration;
 use super::GameGrid;
+use constraints::{Constraint, GenerationConstraint};
 use rand::prelude::*;
-use rand;
-use hexgrid::hex_coordinates::HexCoord;
+use rand::rngs::OsRng;
+use super::rng::GameRng;
 
-pub fn generate_tiles(config: &configuration::MapGenerationSettings) -> Result<GameGrid, ()> {
+pub mod constraints;
+pub mod incremental;
+mod solver;
+mod spiral;
+
+/// Why map generation failed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GenerationError {
+    /// The settings don't make sense. Each string describes one problem; see
+    /// `MapGenerationSettings::validate`.
+    InvalidSettings(Vec<String>),
+    /// There is no way to fill in the map that follows all of the constraints.
+    Unsatisfiable,
+    /// Generation was stopped with a `CancelToken`. See `incremental`.
+    Cancelled,
+    /// Generation took more steps or time than its `Budget` allowed. See `incremental`.
+    OutOfBudget,
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerationError::InvalidSettings(problems) => write!(f, "Invalid map settings: {}", problems.join("; ")),
+            GenerationError::Unsatisfiable => write!(f, "No map fits the map settings"),
+            GenerationError::Cancelled => write!(f, "Map generation was cancelled"),
+            GenerationError::OutOfBudget => write!(f, "Map generation took too long"),
+        }
+    }
+}
+
+pub fn generate_tiles(config: &configuration::MapGenerationSettings) -> Result<GameGrid, GenerationError> {
     let mut grid = GameGrid::new();
+    generate_tiles_into(config, &mut grid)?;
+    place_pinned_ports(config, &mut grid);
+    Ok(grid)
+}
+
+/// Same as `generate_tiles`, but fills in an existing grid instead of creating a `GameGrid`, so
+/// that any grid storage can be used. Every tile in `config.coords` is overwritten. Pinned ports
+/// are not placed, since the grid might not store edges; see `place_pinned_ports`.
+pub fn generate_tiles_into<G>(config: &configuration::MapGenerationSettings, grid: &mut G) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>
+{
+    generate_tiles_with(config, grid, &[])
+}
+
+/// Same as `generate_tiles_into`, but with extra constraints that aren't part of the settings.
+pub fn generate_tiles_with<G>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G,
+    extra_constraints: &[&dyn Constraint<G>]
+) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>
+{
+    fill_tiles(config, grid, extra_constraints, &mut OsRng)
+}
+
+fn fill_tiles<G, R>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G,
+    extra_constraints: &[&dyn Constraint<G>],
+    rng: &mut R
+) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>,
+    R: Rng
+{
+    let (coords, tiles) = prepare_tiles(config, grid)?;
+    let constraints = config.all_constraints();
+    let check = TileCheck::new(config, &constraints, extra_constraints);
+    solver::solve(
+        rng,
+        grid,
+        &coords,
+        &tiles,
+        |grid, coord, tile_type| check.allowed(grid, coord, tile_type),
+        |grid, coord, tile_type| check.place(grid, coord, tile_type),
+        |grid, coord| check.remove(grid, coord)
+    )
+}
+
+/// Coordinates that are left to fill in, and the values to fill them with.
+type ToPlace<V> = (Vec<hex_coordinates::Tile>, Vec<V>);
+
+/// Clear the tiles in `config.coords` and place the pinned tiles. Returns the coordinates that
+/// are left to fill in, and the tiles to fill them with.
+fn prepare_tiles<G>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G
+) -> Result<ToPlace<TileType>, GenerationError>
+where
+    G: GridMut<TileData = Tile>
+{
+    config.validate().map_err(GenerationError::InvalidSettings)?;
+
     let mut tiles: Vec<TileType> = Vec::with_capacity(
         (config.wood_count + config.wheat_count + config.clay_count + config.sheep_count +
             config.stone_count + config.desert_count + config.gold_count + config.ocean_count) as usize
@@ -22,230 +120,315 @@ pub fn generate_tiles(config: &configuration::MapGenerationSettings) -> Result<G
     (0 .. config.desert_count).for_each(|_| tiles.push(TileType::Desert));
     (0 .. config.ocean_count).for_each(|_| tiles.push(TileType::Ocean));
 
-    let mut rng = rand::rngs::OsRng;
-    tiles.shuffle(&mut rng);
-
-    let mut has_started_ocean = false;
-
-    let mut place = |(grid, has_started_ocean): &mut (&mut GameGrid, &mut bool), coord: &hex_coordinates::Tile, new_tile_type: &TileType| {
-        let num_same_neighbors = grid
-            .get_tile_neighbors(coord)
-            .into_iter()
-            .filter(|(_, d)| &d.tile_type == new_tile_type)
-            .count();
-        let placement_good = match new_tile_type {
-            TileType::Ocean => !**has_started_ocean || num_same_neighbors > 0,
-            _ => !config.avoid_adjacent || num_same_neighbors == 0
-        };
-
-        if placement_good {
-            grid.tiles.insert(coord.clone(), Tile{
-                tile_type: *new_tile_type,
-                number: None,
-                thief: false,
-                faceup: false
-            });
-            if *new_tile_type == TileType::Ocean {
-                **has_started_ocean = true;
-            }
-            Ok(())
-        } else {
-            Err(())
+    for coord in config.coords.iter() {
+        grid.remove_tile(coord);
+    }
+
+    // Pinned tiles are placed first, so that the constraints on their neighbors take them into
+    // account.
+    for pinned in config.pinned_tiles.iter() {
+        if let Some(i) = tiles.iter().position(|t| *t == pinned.tile_type) {
+            tiles.remove(i);
         }
-    };
+        grid.insert_tile(pinned.coord, Tile {
+            tile_type: pinned.tile_type,
+            number: None,
+            thief: false,
+            faceup: false
+        });
+    }
+    let coords: Vec<hex_coordinates::Tile> = config.coords.iter()
+        .filter(|c| config.pinned_tile(c).is_none())
+        .cloned()
+        .collect();
+
+    Ok((coords, tiles))
+}
+
+/// Checks tiles against the constraints while the solver places them.
+///
+/// The solver checks every kind of land at a coordinate one after another. The constraints that
+/// only look at whether tiles are land or ocean are also the slowest ones, so their answer for
+/// land is kept until the grid changes.
+struct TileCheck<'a, G> {
+    config: &'a configuration::MapGenerationSettings,
+    constraints: &'a [GenerationConstraint],
+    extra_constraints: &'a [&'a dyn Constraint<G>],
+    /// The last coordinate checked for land, and whether those constraints allowed it.
+    land: Cell<Option<(hex_coordinates::Tile, bool)>>,
+}
+
+impl<'a, G: GridMut<TileData = Tile>> TileCheck<'a, G> {
+    fn new(
+        config: &'a configuration::MapGenerationSettings,
+        constraints: &'a [GenerationConstraint],
+        extra_constraints: &'a [&'a dyn Constraint<G>]
+    ) -> TileCheck<'a, G> {
+        TileCheck { config, constraints, extra_constraints, land: Cell::new(None) }
+    }
 
-    let mut remove = |(grid, has_started_ocean): &mut (&mut GameGrid, &mut bool), coord: &hex_coordinates::Tile| {
-        grid.tiles.remove(coord);
-        let num_oceans = grid.tiles.iter().filter(|(_, d)|{
-            d.tile_type == TileType::Ocean
-        }).count();
-        if num_oceans <= 0 {
-            **has_started_ocean = false;
+    fn allowed(&self, grid: &G, coord: &hex_coordinates::Tile, tile_type: &TileType) -> bool {
+        let allows = |c: &GenerationConstraint| c.allows_tile(self.config, grid, coord, *tile_type);
+        let type_allowed = self.constraints.iter().filter(|c| !c.ignores_land_type()).all(allows)
+            && self.extra_constraints.iter().all(|c| c.allows_tile(self.config, grid, coord, *tile_type));
+        if !type_allowed {
+            return false;
         }
-    };
 
-    let mut iterations = 1000;
-    let mut tries = 0;
-
-    while tries < 100 {
-        match recurse(
-            &mut (&mut grid, &mut has_started_ocean),
-            config.coords.as_slice(),
-            tiles.as_mut_slice(),
-            &mut iterations,
-            &mut place,
-            &mut remove
-        ) {
-            Ok(_) => return Ok(grid),
-            Err(_) => {
-                tries += 1;
-                iterations = 1000;
-                tiles.shuffle(&mut rng);
-                has_started_ocean = false;
-                grid.tiles.clear();
-                println!("Generate tiles, try {}", tries);
+        let kind_allowed = || self.constraints.iter().filter(|c| c.ignores_land_type()).all(allows);
+        if *tile_type == TileType::Ocean {
+            return kind_allowed();
+        }
+        match self.land.get() {
+            Some((last, allowed)) if last == *coord => allowed,
+            _ => {
+                let allowed = kind_allowed();
+                self.land.set(Some((*coord, allowed)));
+                allowed
             }
         }
     }
 
-    Err(())
+    fn place(&self, grid: &mut G, coord: &hex_coordinates::Tile, tile_type: &TileType) {
+        self.land.set(None);
+        grid.insert_tile(*coord, Tile{
+            tile_type: *tile_type,
+            number: None,
+            thief: false,
+            faceup: false
+        });
+    }
+
+    fn remove(&self, grid: &mut G, coord: &hex_coordinates::Tile) {
+        self.land.set(None);
+        grid.remove_tile(coord);
+    }
 }
 
-pub fn generate_numbers(config: &configuration::MapGenerationSettings, grid: &mut GameGrid) -> Result<(), ()> {
-    let mut coords: Vec<hex_coordinates::Tile> = grid.tiles.iter()
+pub fn generate_numbers<G>(config: &configuration::MapGenerationSettings, grid: &mut G) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>
+{
+    generate_numbers_with(config, grid, &[])
+}
+
+/// Same as `generate_numbers`, but with extra constraints that aren't part of the settings.
+pub fn generate_numbers_with<G>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G,
+    extra_constraints: &[&dyn Constraint<G>]
+) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>
+{
+    fill_numbers(config, grid, extra_constraints, &mut OsRng)
+}
+
+fn fill_numbers<G, R>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G,
+    extra_constraints: &[&dyn Constraint<G>],
+    rng: &mut R
+) -> Result<(), GenerationError>
+where
+    G: GridMut<TileData = Tile>,
+    R: Rng
+{
+    let (coords, numbers) = match prepare_numbers(config, grid, rng)? {
+        Some(to_place) => to_place,
+        None => return Ok(()),
+    };
+    let constraints = config.all_constraints();
+    let allowed = |grid: &G, coord: &hex_coordinates::Tile, number: &i32| {
+        number_allowed(config, &constraints, extra_constraints, grid, coord, number)
+    };
+    solver::solve(rng, grid, &coords, &numbers, allowed, place_number, remove_number)
+}
+
+/// Clear the numbers on the board and place the pinned numbers. For `NumberPlacement::Random`,
+/// returns the coordinates that are left to fill in and the numbers to fill them with. The other
+/// placement modes don't need to search, so they place the rest of the numbers right away and
+/// return `None`.
+fn prepare_numbers<G, R>(
+    config: &configuration::MapGenerationSettings,
+    grid: &mut G,
+    rng: &mut R
+) -> Result<Option<ToPlace<i32>>, GenerationError>
+where
+    G: GridMut<TileData = Tile>,
+    R: Rng
+{
+    config.validate().map_err(GenerationError::InvalidSettings)?;
+
+    let mut coords: Vec<hex_coordinates::Tile> = grid.iter_tiles()
         .filter_map(|(c, d)| match d.tile_type {
-            TileType::Resource(_) => Some(c.clone()),
+            TileType::Resource(_) => Some(c),
             _ => None
         })
         .collect();
-    let mut numbers = config.numbers.clone();
+    // Tiles come out of the grid in no particular order, but the same seed should always give
+    // the same numbers.
+    coords.sort_by_key(|c| (c.y, c.x));
 
-    let mut rng = rand::rngs::OsRng;
-    coords.shuffle(&mut rng);
-
-    let mut place = |grid: &mut GameGrid, coord: &hex_coordinates::Tile, new_num: &i32| {
-        let corner_scores: Vec<i32> = coord
-            .get_corner_neighbors()
-            .into_iter()
-            .map(|corner| {
-                get_corner_score(grid, corner)
-            })
-            .flatten()
-            .collect();
-
-        let min_corner_score = corner_scores.iter().min().unwrap_or(&100) + prob(*new_num);
-        let max_corner_score = corner_scores.iter().max().unwrap_or(&0) + prob(*new_num);
-
-        if min_corner_score >= config.min_corner_score && max_corner_score <= config.max_corner_score {
-            let mut tile = grid.tiles.get_mut(coord);
-            match tile {
-                Some(mut tile) => {
-                    tile.number = Some(*new_num);
-                    Ok(())
-                },
-                None => Err(())
-            }
-        } else {
-            Err(())
+    for coord in coords.iter() {
+        if let Some(tile) = grid.get_tile_mut(coord) {
+            tile.number = None;
         }
-    };
+    }
 
-    let mut remove = |grid: &mut GameGrid, coord: &hex_coordinates::Tile| {
-        match grid.tiles.get_mut(&coord) {
-            Some(mut tile) => tile.number = None,
-            _ => {}
+    let mut numbers = config.numbers.clone();
+    for pinned in config.pinned_tiles.iter() {
+        if let (Some(number), Some(tile)) = (pinned.number, grid.get_tile_mut(&pinned.coord)) {
+            tile.number = Some(number);
+            if let Some(i) = numbers.iter().position(|n| *n == number) {
+                numbers.remove(i);
+            }
         }
-    };
+    }
+    coords.retain(|c| config.pinned_tile(c).map_or(true, |p| p.number.is_none()));
 
-    let mut iterations = 1000;
-    let mut tries = 0;
-
-    while tries < 100 {
-        match recurse(
-            grid,
-            coords.as_slice(),
-            numbers.as_mut_slice(),
-            &mut iterations,
-            &mut place,
-            &mut remove
-        ) {
-            Ok(_) => return Ok(()),
-            Err(_) => {
-                iterations = 1000;
-                tries += 1;
-                numbers.shuffle(&mut rng);
-                grid.tiles.iter_mut().for_each(|(_, d)| d.number = None);
-                println!("Generate numbers: try {}", tries);
+    let layout: Vec<(hex_coordinates::Tile, i32)> = match &config.number_placement {
+        configuration::NumberPlacement::Random => return Ok(Some((coords, numbers))),
+        configuration::NumberPlacement::Spiral { start, sequence } => {
+            let land: Vec<hex_coordinates::Tile> = grid.iter_tiles()
+                .filter(|(_, d)| d.tile_type != TileType::Ocean)
+                .map(|(c, _)| c)
+                .collect();
+            // The spiral goes over every land tile, so that it has the same shape no matter
+            // where the desert ended up.
+            let path: Vec<hex_coordinates::Tile> = spiral::spiral(rng, &land, *start)
+                .into_iter()
+                .filter(|c| coords.contains(c))
+                .collect();
+            if path.len() > sequence.len() {
+                return Err(GenerationError::Unsatisfiable);
             }
+            path.into_iter().zip(sequence.iter().cloned()).collect()
+        },
+        configuration::NumberPlacement::Fixed { numbers } => coords.iter()
+            .map(|c| numbers.get(c).map(|n| (*c, *n)).ok_or(GenerationError::Unsatisfiable))
+            .collect::<Result<_, _>>()?,
+    };
+
+    for (coord, number) in layout {
+        if let Some(tile) = grid.get_tile_mut(&coord) {
+            tile.number = Some(number);
         }
     }
+    Ok(None)
+}
 
-    Err(())
+fn number_allowed<G>(
+    config: &configuration::MapGenerationSettings,
+    constraints: &[GenerationConstraint],
+    extra_constraints: &[&dyn Constraint<G>],
+    grid: &G,
+    coord: &hex_coordinates::Tile,
+    number: &i32
+) -> bool
+where
+    G: GridMut<TileData = Tile>
+{
+    constraints.iter().all(|c| c.allows_number(config, grid, coord, *number))
+        && extra_constraints.iter().all(|c| c.allows_number(config, grid, coord, *number))
 }
 
-fn prob(n: i32) -> i32 {
-    match n {
-        2 | 12 => 1,
-        3 | 11 => 2,
-        4 | 10 => 3,
-        5 | 9 => 4,
-        6 | 8 => 5,
-        _ => 0
+fn place_number<G: GridMut<TileData = Tile>>(grid: &mut G, coord: &hex_coordinates::Tile, number: &i32) {
+    if let Some(tile) = grid.get_tile_mut(coord) {
+        tile.number = Some(*number);
     }
 }
 
-fn get_corner_score(grid: &GameGrid, corner: hex_coordinates::Corner) -> Option<i32> {
-    let mut sum = 0;
-    let mut count = 0;
-    for tile in corner.get_tile_neighbors() {
-        if let Some(t) = grid.tiles.get(&tile) {
-            if let Some(n) = t.number {
-                sum += prob(n);
-                count += 1
+fn remove_number<G: GridMut<TileData = Tile>>(grid: &mut G, coord: &hex_coordinates::Tile) {
+    if let Some(tile) = grid.get_tile_mut(coord) {
+        tile.number = None;
+    }
+}
+
+/// Put every port in `config.pinned_ports` on the grid. Roads already on those edges are kept.
+pub fn place_pinned_ports<G>(config: &configuration::MapGenerationSettings, grid: &mut G)
+where
+    G: GridMut<EdgeData = Edge>
+{
+    for pinned in config.pinned_ports.iter() {
+        match grid.get_edge_mut(&pinned.edge) {
+            Some(edge) => edge.port = Some(pinned.port),
+            None => {
+                grid.insert_edge(pinned.edge, Edge { port: Some(pinned.port), road: Road::None, shoal: None });
             }
         }
     }
+}
 
-    if count < 2 {
-        None
-    } else {
-        Some(sum)
-    }
+/// Generate the tiles and numbers of a map from a seed. The same seed and settings always give
+/// the same map, on every platform. The seed goes through `GameRng`, whose output is fixed, unlike
+/// the generators in `rand`, which can change between versions.
+pub fn generate_seeded(config: &configuration::MapGenerationSettings, seed: u64) -> Result<GameGrid, GenerationError> {
+    let mut rng = GameRng::seed_from_u64(seed);
+    let mut grid = GameGrid::new();
+    fill_tiles(config, &mut grid, &[], &mut rng)?;
+    fill_numbers(config, &mut grid, &[], &mut rng)?;
+    place_pinned_ports(config, &mut grid);
+    Ok(grid)
+}
+
+/// A map picked by `generate_best`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub grid: GameGrid,
+    /// Pass this to `generate_seeded` to get the same map again, on any platform.
+    pub seed: u64,
+    /// What the objective function returned for this map.
+    pub score: f64,
 }
 
-fn recurse<State, Coord, FillType, P, R>(
-    state: &mut State,
-    coords: &[Coord],
-    fill_data: &mut [FillType],
-    iterations_left: &mut i32,
-    place: &mut P,
-    remove: &mut R
-) -> Result<(), ()>
+/// Generate `n` maps, and return the one that `objective` gives the highest score. A score of NaN
+/// counts as lower than any other score. On native targets, the maps are generated in parallel.
+///
+/// Fails with `GenerationError::InvalidSettings` if `n` is 0, since there is nothing to pick from.
+///
+/// ```
+/// use catan_lib::{analysis, configuration::MapGenerationSettings, generation};
+///
+/// let config = MapGenerationSettings::defaults_vanilla();
+/// let best = generation::generate_best(&config, 4, |grid| analysis::analyze(grid).balance_index).unwrap();
+/// assert_eq!(generation::generate_seeded(&config, best.seed).unwrap(), best.grid);
+/// ```
+pub fn generate_best<F>(config: &configuration::MapGenerationSettings, n: usize, objective: F) -> Result<Candidate, GenerationError>
 where
-    Coord: hex_coordinates::HexCoord,
-    FillType: Clone + Eq,
-    P: FnMut(&mut State, &Coord, &FillType) -> Result<(), ()>,
-    R: FnMut(&mut State, &Coord)
+    F: Fn(&GameGrid) -> f64 + Sync
 {
-    if coords.len() == 0 || fill_data.len() == 0 {
-        return Ok(())
+    if n == 0 {
+        return Err(GenerationError::InvalidSettings(vec!["Can't pick the best of 0 maps".to_string()]));
     }
+    let seeds: Vec<u64> = (0..n).map(|_| OsRng.gen()).collect();
+    let candidate = |seed: &u64| generate_seeded(config, *seed).map(|grid| Candidate {
+        score: objective(&grid),
+        grid,
+        seed: *seed
+    });
 
-    let mut data_already_tried: Vec<FillType> = Vec::new();
-    let coord = &coords[0];
-
-    for i in 0..fill_data.len() {
-        *iterations_left -= 1;
-        if *iterations_left <= 0 {
-            return Err(())
-        }
+    #[cfg(not(target_arch = "wasm32"))]
+    let candidates: Vec<Result<Candidate, GenerationError>> = {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = (seeds.len() + threads - 1) / threads;
+        let candidate = &candidate;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = seeds.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(candidate).collect::<Vec<_>>()))
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        })
+    };
+    #[cfg(target_arch = "wasm32")]
+    let candidates: Vec<Result<Candidate, GenerationError>> = seeds.iter().map(candidate).collect();
 
-        if data_already_tried.contains(&fill_data[i]) {
-            continue;
+    let mut best: Option<Candidate> = None;
+    for candidate in candidates {
+        let candidate = candidate?;
+        if best.as_ref().map_or(true, |b| b.score.is_nan() || candidate.score > b.score) {
+            best = Some(candidate);
         }
-
-        match place(state, coord, &fill_data[i]) {
-            Ok(_) => {
-                data_already_tried.push(fill_data[i].clone());
-                fill_data.swap(0, i);
-                match recurse(
-                    state,
-                    &coords[1..],
-                    &mut fill_data[1..],
-                    iterations_left,
-                    place,
-                    remove
-                ) {
-                    Ok(_) => return Ok(()),
-                    Err(_) => {
-                        fill_data.swap(0, i);
-                        remove(state, coord);
-                    }
-                }
-            },
-            Err(_) => { }
-        };
     }
-
-    Err(())
-}
\ No newline at end of file
+    Ok(best.unwrap())
+}
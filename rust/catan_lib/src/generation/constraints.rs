@@ -0,0 +1,442 @@
+//! Rules that a randomly generated map has to follow.
+//!
+//! Constraints are checked every time the generator tries to place a tile or a number, while the
+//! map is only partly filled in. So they should only reject a placement if it definitely can't
+//! lead to a good map, no matter how the rest of the map is filled in.
+
+use std::collections::{HashSet, VecDeque};
+use serde::{Serialize, Deserialize};
+use hexgrid::Grid;
+use hexgrid::hex_coordinates::{self, HexCoord};
+use crate::analysis::{pips, corner_score};
+use crate::types::{Tile, TileType, Resource};
+use crate::configuration::MapGenerationSettings;
+
+/// A rule for map generation. Both functions default to allowing everything, so a constraint
+/// only needs to implement the one it cares about.
+///
+/// `grid` contains everything that has been placed so far. It does not include the tile or
+/// number being checked.
+pub trait Constraint<G: Grid<TileData = Tile>> {
+    /// Can a tile of this type go at these coordinates?
+    fn allows_tile(&self, _config: &MapGenerationSettings, _grid: &G, _coord: &hex_coordinates::Tile, _tile_type: TileType) -> bool {
+        true
+    }
+
+    /// Can this number go on the (already placed) tile at these coordinates?
+    fn allows_number(&self, _config: &MapGenerationSettings, _grid: &G, _coord: &hex_coordinates::Tile, _number: i32) -> bool {
+        true
+    }
+}
+
+/// The constraints that come with the game, which can be listed in `MapGenerationSettings`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GenerationConstraint {
+    /// No two adjacent tiles have the same type, except for oceans.
+    AvoidAdjacent,
+    /// All of the oceans are connected to each other.
+    ContiguousOcean,
+    /// The number score of every corner touching at least two numbers is within these bounds.
+    /// See `MapGenerationSettings::min_corner_score`.
+    CornerScore { min: i32, max: i32 },
+    /// None of these numbers are next to each other (or to themselves), like the usual rule
+    /// that 6 and 8 can't touch.
+    NoAdjacentNumbers { numbers: Vec<i32> },
+    /// No two adjacent tiles have the same number.
+    NoSameNumberAdjacent,
+    /// For each resource, the total pips on all tiles of that resource are at most `max`.
+    MaxResourcePips { max: i32 },
+    /// The tile closest to the middle of the map is a desert.
+    DesertInCenter,
+    /// Every island (group of connected tiles that aren't ocean) has at least `min` tiles.
+    MinIslandSize { min: u32 },
+    /// Every island has at most `max` tiles.
+    MaxIslandSize { max: u32 },
+    /// The number of islands is within these bounds.
+    IslandCount { min: u32, max: u32 },
+    /// At least one island has `min` tiles or more, so there is somewhere big enough for every
+    /// player to start.
+    MainIsland { min: u32 },
+    /// Islands are separated by at least `min` oceans. Separate islands never touch, so this only
+    /// matters above 1.
+    WaterGap { min: u32 },
+    /// Every island has at least `min` different resources.
+    IslandResources { min: u32 },
+}
+
+impl<G: Grid<TileData = Tile>> Constraint<G> for GenerationConstraint {
+    fn allows_tile(&self, config: &MapGenerationSettings, grid: &G, coord: &hex_coordinates::Tile, tile_type: TileType) -> bool {
+        match self {
+            GenerationConstraint::AvoidAdjacent => {
+                tile_type == TileType::Ocean || grid.get_tile_neighbors(coord)
+                    .iter()
+                    .all(|(_, d)| d.tile_type != tile_type)
+            },
+            GenerationConstraint::ContiguousOcean => {
+                oceans_can_connect(config, grid, coord, tile_type)
+            },
+            GenerationConstraint::DesertInCenter => {
+                center(&config.coords).as_ref() != Some(coord) || tile_type == TileType::Desert
+            },
+            GenerationConstraint::MinIslandSize { min } => {
+                let after = After { config, grid, coord, tile_type };
+                after.islands_around().iter().all(|i| !i.finished || i.tiles.len() >= *min as usize)
+            },
+            GenerationConstraint::MaxIslandSize { max } => {
+                let after = After { config, grid, coord, tile_type };
+                !after.is_land(coord) || after.island(*coord).tiles.len() <= *max as usize
+            },
+            GenerationConstraint::IslandCount { min, max } => {
+                // The count can only go over `max` when this tile finishes an island, and can only
+                // be checked against `min` once the map is full.
+                let after = After { config, grid, coord, tile_type };
+                let filled = after.all_filled();
+                if !filled && !after.islands_around().iter().any(|i| i.finished) {
+                    return true;
+                }
+                let islands = after.all_islands();
+                islands.iter().filter(|i| i.finished).count() <= *max as usize
+                    && (!filled || islands.len() >= *min as usize)
+            },
+            GenerationConstraint::MainIsland { min } => {
+                // Only an ocean can take away room from an island.
+                tile_type != TileType::Ocean
+                    || After { config, grid, coord, tile_type }.room_for_island(*min as usize)
+            },
+            GenerationConstraint::WaterGap { min } => {
+                water_gap_ok(&After { config, grid, coord, tile_type }, *min)
+            },
+            GenerationConstraint::IslandResources { min } => {
+                let after = After { config, grid, coord, tile_type };
+                after.islands_around().iter().all(|i| !i.finished || resource_kinds(&after, i) >= *min)
+            },
+            _ => true
+        }
+    }
+
+    fn allows_number(&self, _config: &MapGenerationSettings, grid: &G, coord: &hex_coordinates::Tile, number: i32) -> bool {
+        match self {
+            GenerationConstraint::CornerScore { min, max } => {
+                let corner_scores: Vec<i32> = coord
+                    .get_corner_neighbors()
+                    .into_iter()
+                    .filter_map(|corner| corner_score(grid, &corner))
+                    .collect();
+
+                let min_corner_score = corner_scores.iter().min().unwrap_or(&100) + pips(number);
+                let max_corner_score = corner_scores.iter().max().unwrap_or(&0) + pips(number);
+                min_corner_score >= *min && max_corner_score <= *max
+            },
+            GenerationConstraint::NoAdjacentNumbers { numbers } => {
+                !numbers.contains(&number) || grid.get_tile_neighbors(coord)
+                    .iter()
+                    .all(|(_, d)| d.number.map_or(true, |n| !numbers.contains(&n)))
+            },
+            GenerationConstraint::NoSameNumberAdjacent => {
+                grid.get_tile_neighbors(coord).iter().all(|(_, d)| d.number != Some(number))
+            },
+            GenerationConstraint::MaxResourcePips { max } => {
+                let tile_type = match grid.get_tile(coord) {
+                    Some(tile) => tile.tile_type,
+                    None => return true
+                };
+                let resource_pips: i32 = grid.iter_tiles()
+                    .filter(|(c, d)| c != coord && d.tile_type == tile_type)
+                    .filter_map(|(_, d)| d.number.map(pips))
+                    .sum();
+                resource_pips + pips(number) <= *max
+            },
+            _ => true
+        }
+    }
+}
+
+impl GenerationConstraint {
+    /// Whether `allows_tile` only cares if a tile is land or ocean, and gives the same answer for
+    /// every kind of land.
+    pub(crate) fn ignores_land_type(&self) -> bool {
+        matches!(self,
+            GenerationConstraint::ContiguousOcean
+            | GenerationConstraint::MinIslandSize { .. }
+            | GenerationConstraint::MaxIslandSize { .. }
+            | GenerationConstraint::IslandCount { .. }
+            | GenerationConstraint::MainIsland { .. }
+            | GenerationConstraint::WaterGap { .. }
+        )
+    }
+}
+
+/// The coordinates closest to the middle of all of these coordinates.
+fn center(coords: &[hex_coordinates::Tile]) -> Option<hex_coordinates::Tile> {
+    // Convert to cartesian coordinates, so that distances are the same in every direction.
+    let to_point = |c: &hex_coordinates::Tile| (c.x as f64 + c.y as f64 / 2.0, c.y as f64 * 3f64.sqrt() / 2.0);
+    let n = coords.len() as f64;
+    let mean_x = coords.iter().map(|c| to_point(c).0).sum::<f64>() / n;
+    let mean_y = coords.iter().map(|c| to_point(c).1).sum::<f64>() / n;
+    let distance = |c: &hex_coordinates::Tile| {
+        let (x, y) = to_point(c);
+        (x - mean_x).powi(2) + (y - mean_y).powi(2)
+    };
+
+    coords.iter()
+        .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+        .cloned()
+}
+
+/// Check that after placing this tile, the oceans can still all be joined up. Tiles are filled in
+/// in any order, so two oceans are fine as long as there is a path between them through other
+/// oceans or through coordinates that haven't been filled in yet.
+///
+/// This assumes the oceans could all be joined up before this tile, which is true as long as
+/// every tile so far was placed with this constraint.
+fn oceans_can_connect<G: Grid<TileData = Tile>>(
+    config: &MapGenerationSettings,
+    grid: &G,
+    coord: &hex_coordinates::Tile,
+    tile_type: TileType
+) -> bool {
+    let is_ocean = |c: &hex_coordinates::Tile| {
+        grid.get_tile(c).map_or(false, |d| d.tile_type == TileType::Ocean)
+    };
+    // Whether a path between oceans can go through these coordinates.
+    let is_open = |c: &hex_coordinates::Tile| {
+        c != coord && match grid.get_tile(c) {
+            Some(d) => d.tile_type == TileType::Ocean,
+            None => config.coords.contains(c)
+        }
+    };
+
+    let other_oceans = || -> Vec<hex_coordinates::Tile> {
+        grid.iter_tiles()
+            .filter(|(c, d)| c != coord && d.tile_type == TileType::Ocean)
+            .map(|(c, _)| c)
+            .collect()
+    };
+
+    if tile_type == TileType::Ocean {
+        // The other oceans are already joined up, so a new ocean only has to reach one of them.
+        return coord.get_tile_neighbors().iter().any(is_ocean)
+            || other_oceans().is_empty()
+            || search_open(coord, is_open, is_ocean);
+    }
+
+    // Land only gets in the way if it splits the open tiles around it into separate groups.
+    // Neighbors next to each other in this list are also next to each other, so each run of open
+    // neighbors is joined up without going through this tile.
+    let ring = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)]
+        .map(|(dx, dy)| hex_coordinates::Tile::new(coord.x + dx, coord.y + dy));
+    let open = ring.map(|c| is_open(&c));
+    let runs: Vec<hex_coordinates::Tile> = (0..ring.len())
+        .filter(|i| open[*i] && !open[(i + 1) % ring.len()])
+        .map(|i| ring[i])
+        .collect();
+    if runs.len() <= 1 {
+        return true;
+    }
+
+    let oceans = other_oceans();
+    if oceans.is_empty() {
+        return true;
+    }
+
+    // Usually the runs are still joined up some other way, and that's quick to find. If not, the
+    // search has gone over every open coordinate on the side of the first run.
+    let mut runs_left = runs.len() - 1;
+    let mut side = vec![runs[0]];
+    if search_open(&runs[0], is_open, |c| {
+        side.push(*c);
+        if runs[1..].contains(c) {
+            runs_left -= 1;
+        }
+        runs_left == 0
+    }) {
+        return true;
+    }
+
+    // Every ocean has to be on the same side. If none of them are on the first run's side, they
+    // could still all be together on one of the others.
+    let on_side = oceans.iter().filter(|c| side.contains(c)).count();
+    if on_side > 0 {
+        return on_side == oceans.len();
+    }
+    let mut seen = oceans[0..1].to_vec();
+    search_open(&oceans[0], is_open, |c| {
+        seen.push(*c);
+        false
+    });
+    oceans.iter().all(|c| seen.contains(c))
+}
+
+/// Breadth first search from `start` through open coordinates, until `found` returns true.
+/// Returns false if it never does.
+fn search_open<O, F>(start: &hex_coordinates::Tile, is_open: O, mut found: F) -> bool
+where
+    O: Fn(&hex_coordinates::Tile) -> bool,
+    F: FnMut(&hex_coordinates::Tile) -> bool
+{
+    // Maps are small, so a list is quicker than hashing.
+    let mut seen = vec![*start];
+    let mut queue = VecDeque::new();
+    queue.push_back(*start);
+    while let Some(tile) = queue.pop_front() {
+        for neighbor in tile.get_tile_neighbors() {
+            if !seen.contains(&neighbor) && is_open(&neighbor) {
+                seen.push(neighbor);
+                if found(&neighbor) {
+                    return true;
+                }
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    false
+}
+
+/// The map as it would be after placing a tile, for constraints that look at whole islands.
+struct After<'a, G> {
+    config: &'a MapGenerationSettings,
+    grid: &'a G,
+    coord: &'a hex_coordinates::Tile,
+    tile_type: TileType,
+}
+
+/// A group of connected tiles that aren't ocean.
+struct Island {
+    tiles: Vec<hex_coordinates::Tile>,
+    /// None of the tiles are next to coordinates that haven't been filled in yet, so the island
+    /// can't change any more.
+    finished: bool,
+}
+
+impl<'a, G: Grid<TileData = Tile>> After<'a, G> {
+    fn tile_type(&self, c: &hex_coordinates::Tile) -> Option<TileType> {
+        if c == self.coord {
+            Some(self.tile_type)
+        } else {
+            self.grid.get_tile(c).map(|d| d.tile_type)
+        }
+    }
+
+    fn is_land(&self, c: &hex_coordinates::Tile) -> bool {
+        self.tile_type(c).map_or(false, |t| t != TileType::Ocean)
+    }
+
+    fn is_empty(&self, c: &hex_coordinates::Tile) -> bool {
+        self.tile_type(c).is_none() && self.config.coords.contains(c)
+    }
+
+    fn island(&self, start: hex_coordinates::Tile) -> Island {
+        let mut tiles = vec![start];
+        let mut finished = true;
+        let mut i = 0;
+        while i < tiles.len() {
+            for neighbor in tiles[i].get_tile_neighbors() {
+                if self.is_empty(&neighbor) {
+                    finished = false;
+                } else if self.is_land(&neighbor) && !tiles.contains(&neighbor) {
+                    tiles.push(neighbor);
+                }
+            }
+            i += 1;
+        }
+        Island { tiles, finished }
+    }
+
+    /// The islands that the tile is on or next to. Placing a tile can only change these islands.
+    fn islands_around(&self) -> Vec<Island> {
+        let mut starts = self.coord.get_tile_neighbors();
+        starts.push(*self.coord);
+        self.islands(starts)
+    }
+
+    fn all_islands(&self) -> Vec<Island> {
+        let mut starts: Vec<hex_coordinates::Tile> = self.grid.iter_tiles().map(|(c, _)| c).collect();
+        starts.push(*self.coord);
+        self.islands(starts)
+    }
+
+    fn islands(&self, starts: Vec<hex_coordinates::Tile>) -> Vec<Island> {
+        let mut islands: Vec<Island> = Vec::new();
+        for start in starts.into_iter().filter(|c| self.is_land(c)) {
+            if !islands.iter().any(|i| i.tiles.contains(&start)) {
+                islands.push(self.island(start));
+            }
+        }
+        islands
+    }
+
+    fn all_filled(&self) -> bool {
+        self.config.coords.iter().all(|c| !self.is_empty(c))
+    }
+
+    /// Check that an island could still end up with `min` tiles, if enough of the coordinates
+    /// that haven't been filled in yet became land.
+    fn room_for_island(&self, min: usize) -> bool {
+        let mut seen: HashSet<hex_coordinates::Tile> = HashSet::new();
+        let open = |c: &hex_coordinates::Tile| self.is_land(c) || self.is_empty(c);
+        for start in self.config.coords.iter().chain(std::iter::once(self.coord)) {
+            if !open(start) || !seen.insert(*start) {
+                continue;
+            }
+            let mut size = 0;
+            let mut queue = VecDeque::new();
+            queue.push_back(*start);
+            while let Some(tile) = queue.pop_front() {
+                size += 1;
+                if size >= min {
+                    return true;
+                }
+                for neighbor in tile.get_tile_neighbors() {
+                    if open(&neighbor) && seen.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Check that the finished islands around a tile aren't within `min` tiles of any other land.
+///
+/// Land that is placed near another island also has to be able to join up with it later, or the
+/// two would end up too close. Checking that right away rules out a lot of maps early.
+fn water_gap_ok<G: Grid<TileData = Tile>>(after: &After<G>, min: u32) -> bool {
+    if after.is_land(after.coord) {
+        let open = |c: &hex_coordinates::Tile| after.is_land(c) || after.is_empty(c);
+        let near = within(after.coord, min).filter(|c| c != after.coord && after.is_land(c));
+        for other in near {
+            if !search_open(after.coord, open, |c| *c == other) {
+                return false;
+            }
+        }
+    }
+    after.islands_around().iter().filter(|i| i.finished).all(|island| {
+        island.tiles.iter().all(|tile| {
+            within(tile, min).all(|other| !after.is_land(&other) || island.tiles.contains(&other))
+        })
+    })
+}
+
+/// Every tile at most `distance` steps away from `center`, including `center`.
+fn within(center: &hex_coordinates::Tile, distance: u32) -> impl Iterator<Item = hex_coordinates::Tile> {
+    let r = distance as i32;
+    let center = *center;
+    (-r..=r).flat_map(move |dy| {
+        ((-r).max(-r - dy)..=r.min(r - dy)).map(move |dx| hex_coordinates::Tile::new(center.x + dx, center.y + dy))
+    })
+}
+
+/// Count the different resources on an island.
+fn resource_kinds<G: Grid<TileData = Tile>>(after: &After<G>, island: &Island) -> u32 {
+    let mut kinds: Vec<Resource> = Vec::new();
+    for tile in island.tiles.iter() {
+        if let Some(TileType::Resource(resource)) = after.tile_type(tile) {
+            if !kinds.contains(&resource) {
+                kinds.push(resource);
+            }
+        }
+    }
+    kinds.len() as u32
+}
@@ -0,0 +1,292 @@
+//! Generate a map a little at a time, so that a hard map doesn't freeze the program.
+//!
+//! A `Generator` does the same work as `generate_seeded`, but only as many steps as it is told
+//! to at once. In a browser, where nothing else can happen while Rust code is running, call
+//! `Generator::step` from a timer until it finishes. Elsewhere, `Generator::run` keeps stepping
+//! until the map is done, a `Budget` runs out, or it is cancelled with a `CancelToken`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use rand::prelude::*;
+use rand::rngs::OsRng;
+use hexgrid::hex_coordinates;
+use crate::GameGrid;
+use crate::rng::GameRng;
+use crate::configuration::MapGenerationSettings;
+use crate::types::TileType;
+use super::constraints::GenerationConstraint;
+use super::solver::{Solver, Status};
+use super::GenerationError;
+
+/// How many steps `Generator::run` takes between checking the budget, checking for
+/// cancellation, and reporting progress.
+const STEPS_PER_CHECK: usize = 100;
+
+/// Stops a `Generator` from another thread, or from a callback. Clones share the same flag, so
+/// cancelling any of them cancels all of them.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on how long `Generator::run` can take. Generation fails with
+/// `GenerationError::OutOfBudget` if either one runs out.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Budget {
+    /// The most steps to take. Each step fills in a tile or a number, or backs out of one.
+    pub max_steps: Option<usize>,
+    /// The most time to take. In WebAssembly this is measured with the JavaScript clock, so it
+    /// only works in a browser or another JavaScript host.
+    pub max_time: Option<Duration>,
+}
+
+/// Measures how long `Generator::run` has been going. The standard library has no clock in
+/// WebAssembly, so there it asks JavaScript for the time instead.
+struct Timer {
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    started: f64,
+}
+
+impl Timer {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start() -> Timer {
+        Timer { started: std::time::Instant::now() }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn start() -> Timer {
+        Timer { started: js_sys::Date::now() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn elapsed(&self) -> Duration {
+        Duration::from_millis((js_sys::Date::now() - self.started).max(0.0) as u64)
+    }
+}
+
+/// What a `Generator` is working on.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    Tiles,
+    Numbers,
+    Finished,
+}
+
+/// How far along a `Generator` is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    pub phase: Phase,
+    /// How many tiles or numbers are placed right now, not counting pinned ones. This goes down
+    /// as well as up, since the generator backs out of placements that don't work.
+    pub placed: usize,
+    /// How many tiles or numbers there are to place in this phase.
+    pub total: usize,
+    /// Steps taken so far, over both phases.
+    pub steps: usize,
+    /// How many times the generator has started the current phase over, after getting stuck.
+    pub restarts: usize,
+}
+
+enum State {
+    Tiles(Solver<TileType>),
+    Numbers(Solver<i32>),
+    Finished,
+}
+
+/// Generates a map one step at a time. See the module documentation.
+pub struct Generator {
+    config: MapGenerationSettings,
+    constraints: Vec<GenerationConstraint>,
+    grid: GameGrid,
+    rng: GameRng,
+    state: State,
+    cancel: CancelToken,
+    /// Steps taken in phases that are already finished.
+    earlier_steps: usize,
+}
+
+impl Generator {
+    /// Start generating a map from a random seed.
+    pub fn new(config: MapGenerationSettings) -> Result<Generator, GenerationError> {
+        Generator::seeded(config, OsRng.gen())
+    }
+
+    /// Start generating a map from a seed. Once it is finished, the map is the same one that
+    /// `generate_seeded` gives for this seed, no matter how the steps were split up.
+    pub fn seeded(config: MapGenerationSettings, seed: u64) -> Result<Generator, GenerationError> {
+        let mut grid = GameGrid::new();
+        let (coords, tiles) = super::prepare_tiles(&config, &mut grid)?;
+        Ok(Generator {
+            constraints: config.all_constraints(),
+            config,
+            grid,
+            rng: GameRng::seed_from_u64(seed),
+            state: State::Tiles(Solver::new(&coords, &tiles)),
+            cancel: CancelToken::new(),
+            earlier_steps: 0,
+        })
+    }
+
+    /// A token that stops this generator. The next call to `step` or `run` fails with
+    /// `GenerationError::Cancelled`.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    pub fn progress(&self) -> Progress {
+        let (phase, placed, total, steps, restarts) = match &self.state {
+            State::Tiles(s) => (Phase::Tiles, s.placed(), s.total(), s.steps, s.restarts),
+            State::Numbers(s) => (Phase::Numbers, s.placed(), s.total(), s.steps, s.restarts),
+            State::Finished => (Phase::Finished, 0, 0, 0, 0),
+        };
+        Progress { phase, placed, total, steps: self.earlier_steps + steps, restarts }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, State::Finished)
+    }
+
+    /// The map so far. Until the generator is finished, this is only partly filled in.
+    pub fn grid(&self) -> &GameGrid {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> GameGrid {
+        self.grid
+    }
+
+    /// Take at most `steps` more steps. Once the map is finished, this does nothing.
+    pub fn step(&mut self, steps: usize) -> Result<Progress, GenerationError> {
+        if self.cancel.is_cancelled() {
+            return Err(GenerationError::Cancelled);
+        }
+
+        let Generator { config, constraints, grid, rng, state, earlier_steps, .. } = self;
+        let mut budget = steps;
+        loop {
+            let (status, taken) = match state {
+                State::Tiles(solver) => {
+                    let before = solver.steps;
+                    let check = super::TileCheck::new(config, constraints, &[]);
+                    let allowed = |grid: &GameGrid, coord: &hex_coordinates::Tile, tile_type: &TileType| {
+                        check.allowed(grid, coord, tile_type)
+                    };
+                    let mut place = |grid: &mut GameGrid, coord: &hex_coordinates::Tile, tile_type: &TileType| {
+                        check.place(grid, coord, tile_type)
+                    };
+                    let mut remove = |grid: &mut GameGrid, coord: &hex_coordinates::Tile| check.remove(grid, coord);
+                    let status = solver.run(rng, grid, &allowed, &mut place, &mut remove, budget);
+                    (status, solver.steps - before)
+                },
+                State::Numbers(solver) => {
+                    let before = solver.steps;
+                    let allowed = |grid: &GameGrid, coord: &hex_coordinates::Tile, number: &i32| {
+                        super::number_allowed(config, constraints, &[], grid, coord, number)
+                    };
+                    let status = solver.run(rng, grid, &allowed, &mut super::place_number, &mut super::remove_number, budget);
+                    (status, solver.steps - before)
+                },
+                State::Finished => break,
+            };
+            budget -= taken;
+
+            match status {
+                Status::Paused => break,
+                Status::NoSolution => return Err(GenerationError::Unsatisfiable),
+                Status::Solved => {
+                    *earlier_steps += match state {
+                        State::Tiles(s) => s.steps,
+                        State::Numbers(s) => s.steps,
+                        State::Finished => 0,
+                    };
+                    let numbers = match state {
+                        State::Tiles(_) => super::prepare_numbers(config, grid, rng)?,
+                        _ => None,
+                    };
+                    *state = match numbers {
+                        Some((coords, numbers)) => State::Numbers(Solver::new(&coords, &numbers)),
+                        None => {
+                            super::place_pinned_ports(config, grid);
+                            State::Finished
+                        },
+                    };
+                },
+            }
+        }
+        Ok(self.progress())
+    }
+
+    /// Keep stepping until the map is finished. `progress` is called every so often, and once
+    /// more at the end.
+    pub fn run<F>(&mut self, budget: &Budget, mut progress: F) -> Result<(), GenerationError>
+    where
+        F: FnMut(&Progress)
+    {
+        let timer = Timer::start();
+
+        while !self.is_finished() {
+            let steps = match budget.max_steps {
+                Some(max) => {
+                    let left = max.saturating_sub(self.progress().steps);
+                    if left == 0 {
+                        return Err(GenerationError::OutOfBudget);
+                    }
+                    left.min(STEPS_PER_CHECK)
+                },
+                None => STEPS_PER_CHECK,
+            };
+            progress(&self.step(steps)?);
+            if !self.is_finished() && budget.max_time.map_or(false, |max| timer.elapsed() > max) {
+                return Err(GenerationError::OutOfBudget);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generate a map like `generate_tiles` and `generate_numbers`, but give up when the budget runs
+/// out or `cancel` is cancelled. `progress` is called every so often.
+///
+/// ```
+/// use catan_lib::configuration::MapGenerationSettings;
+/// use catan_lib::generation::incremental::{self, Budget, CancelToken};
+///
+/// let config = MapGenerationSettings::defaults_vanilla();
+/// let budget = Budget { max_steps: Some(1_000_000), max_time: None };
+/// let grid = incremental::generate(&config, &budget, &CancelToken::new(), |p| println!("{:?}", p)).unwrap();
+/// assert_eq!(grid.tiles.len(), 19);
+/// ```
+pub fn generate<F>(
+    config: &MapGenerationSettings,
+    budget: &Budget,
+    cancel: &CancelToken,
+    progress: F
+) -> Result<GameGrid, GenerationError>
+where
+    F: FnMut(&Progress)
+{
+    let mut generator = Generator::new(config.clone())?;
+    generator.cancel = cancel.clone();
+    generator.run(budget, progress)?;
+    Ok(generator.into_grid())
+}
@@ -0,0 +1,334 @@
+//! A backtracking constraint satisfaction solver, used to place both tiles and numbers.
+//!
+//! The solver keeps a list of the values that can still go on each empty coordinate. After a
+//! value is placed, it only rechecks the lists of the neighboring coordinates, since most
+//! constraints only look at neighbors (forward checking). Constraints that look further away,
+//! like the island constraints, are checked again when a value is actually placed. If any
+//! coordinate has no options left, the solver backtracks right away. Otherwise, it fills in the
+//! coordinate with the fewest options first (most constrained variable), trying each option in a
+//! random order.
+//!
+//! The search keeps its own stack instead of recursing, so that it can stop after any number
+//! of steps and carry on later.
+//!
+//! A bad choice near the start of the search can take a very long time to back out of, so each
+//! search only gets a limited number of steps. If it runs out, the solver starts over in a new
+//! random order with twice as many steps. A search that tries every option without running out
+//! of steps proves that there is no solution, so the solver always finishes with a definite
+//! answer.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use rand::prelude::*;
+use hexgrid::hex_coordinates::{self, HexCoord};
+use super::GenerationError;
+
+/// Steps allowed per coordinate on the first try.
+const FIRST_TRY_STEPS_PER_COORD: usize = 2;
+
+/// Assign one of `values` to each of `coords`, using each value at most once, so that `allowed`
+/// is true for every value at the time it is placed. If there are more coordinates than values,
+/// the extra coordinates are left empty. If there are more values than coordinates, the extra
+/// values are not used.
+///
+/// Once `allowed` is false for a value at some coordinate, it should stay false as more values
+/// are placed. Otherwise the solver could miss a solution.
+pub(super) fn solve<S, V, A, P, R, N>(
+    rng: &mut N,
+    state: &mut S,
+    coords: &[hex_coordinates::Tile],
+    values: &[V],
+    allowed: A,
+    mut place: P,
+    mut remove: R
+) -> Result<(), GenerationError>
+where
+    V: Clone + Eq,
+    A: Fn(&S, &hex_coordinates::Tile, &V) -> bool,
+    P: FnMut(&mut S, &hex_coordinates::Tile, &V),
+    R: FnMut(&mut S, &hex_coordinates::Tile),
+    N: Rng
+{
+    let mut solver = Solver::new(coords, values);
+    loop {
+        match solver.run(rng, state, &allowed, &mut place, &mut remove, usize::MAX) {
+            Status::Solved => return Ok(()),
+            Status::NoSolution => return Err(GenerationError::Unsatisfiable),
+            Status::Paused => {},
+        }
+    }
+}
+
+pub(super) enum Status {
+    Solved,
+    NoSolution,
+    /// Ran out of the steps given to `Solver::run`. Call it again to carry on.
+    Paused,
+}
+
+/// The state of a search that can be paused and carried on, so that generation can be spread
+/// out over time. See `solve` for what it does.
+pub(super) struct Solver<V> {
+    counts: Vec<(Option<V>, usize)>,
+    coords: Vec<hex_coordinates::Tile>,
+    /// For each coordinate, the other coordinates next to it.
+    neighbors: Vec<Vec<usize>>,
+    /// For each coordinate, the values in `counts` that haven't been ruled out there, in order.
+    /// Values that have run out are still listed. Filled in when the search starts.
+    domains: Vec<Vec<usize>>,
+    filled: Vec<bool>,
+    unassigned: Vec<usize>,
+    /// The coordinates filled in so far, from first to last.
+    stack: Vec<Frame>,
+    /// Steps allowed for the current try.
+    try_steps: usize,
+    steps_left: usize,
+    started: bool,
+    /// Steps taken so far, over every try.
+    pub(super) steps: usize,
+    /// How many times the search has started over.
+    pub(super) restarts: usize,
+}
+
+/// A coordinate that has been filled in, and the options for it that haven't been tried yet.
+struct Frame {
+    coord: usize,
+    /// Where `coord` was in the unassigned list, so it can be put back in the same place.
+    index: usize,
+    options: Vec<usize>,
+    next: usize,
+    placed: Option<usize>,
+    /// The values that placing this one ruled out at its neighbors, to put back when it is
+    /// taken out.
+    ruled_out: Vec<(usize, usize)>,
+}
+
+impl<V: Clone + Eq> Solver<V> {
+    pub(super) fn new(coords: &[hex_coordinates::Tile], values: &[V]) -> Solver<V> {
+        // Identical values are interchangeable, so only keep one copy of each, with a count.
+        // `None` stands for leaving a coordinate empty.
+        let mut counts: Vec<(Option<V>, usize)> = Vec::new();
+        for value in values {
+            match counts.iter_mut().find(|(v, _)| v.as_ref() == Some(value)) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((Some(value.clone()), 1)),
+            }
+        }
+        if coords.len() > values.len() {
+            counts.push((None, coords.len() - values.len()));
+        }
+
+        let index: HashMap<hex_coordinates::Tile, usize> = coords.iter()
+            .enumerate()
+            .map(|(i, c)| (*c, i))
+            .collect();
+        let neighbors = coords.iter()
+            .map(|c| c.get_tile_neighbors().iter().filter_map(|n| index.get(n).cloned()).collect())
+            .collect();
+
+        Solver {
+            counts,
+            coords: coords.to_vec(),
+            neighbors,
+            domains: Vec::new(),
+            filled: vec![false; coords.len()],
+            unassigned: (0..coords.len()).collect(),
+            stack: Vec::new(),
+            try_steps: FIRST_TRY_STEPS_PER_COORD * coords.len().max(1),
+            steps_left: 0,
+            started: false,
+            steps: 0,
+            restarts: 0,
+        }
+    }
+
+    /// How many coordinates are filled in right now.
+    pub(super) fn placed(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// How many coordinates there are to fill in.
+    pub(super) fn total(&self) -> usize {
+        self.coords.len()
+    }
+
+    /// Carry on the search for at most `max_steps` steps. `state` has to be left as it was
+    /// between calls, and the same functions have to be passed in every time.
+    pub(super) fn run<S, A, P, R, N>(
+        &mut self,
+        rng: &mut N,
+        state: &mut S,
+        allowed: &A,
+        place: &mut P,
+        remove: &mut R,
+        max_steps: usize
+    ) -> Status
+    where
+        A: Fn(&S, &hex_coordinates::Tile, &V) -> bool,
+        P: FnMut(&mut S, &hex_coordinates::Tile, &V),
+        R: FnMut(&mut S, &hex_coordinates::Tile),
+        N: Rng
+    {
+        if !self.started {
+            self.started = true;
+            let counts = &self.counts;
+            self.domains = self.coords.iter()
+                .map(|coord| (0..counts.len())
+                    .filter(|v| counts[*v].0.as_ref().map_or(true, |value| allowed(state, coord, value)))
+                    .collect())
+                .collect();
+            self.unassigned.shuffle(rng);
+            self.steps_left = self.try_steps;
+        }
+
+        let mut budget = max_steps;
+        loop {
+            if self.unassigned.is_empty() {
+                return Status::Solved;
+            }
+            if budget == 0 {
+                return Status::Paused;
+            }
+            if self.steps_left == 0 {
+                self.restart(rng, state, remove);
+                continue;
+            }
+            budget -= 1;
+            self.steps_left -= 1;
+            self.steps += 1;
+
+            let mut best: Option<(usize, (usize, Reverse<usize>))> = None;
+            let mut dead_end = false;
+            for (i, coord) in self.unassigned.iter().enumerate() {
+                let options = self.domains[*coord].iter().filter(|v| self.counts[**v].1 > 0).count();
+                if options == 0 {
+                    dead_end = true;
+                    break;
+                }
+                // Break ties with the coordinate that has the most empty neighbors, since it
+                // narrows down the most other coordinates.
+                let empty = self.neighbors[*coord].iter().filter(|n| !self.filled[**n]).count();
+                let key = (options, Reverse(empty));
+                if best.map_or(true, |(_, best_key)| key < best_key) {
+                    best = Some((i, key));
+                }
+            }
+
+            if !dead_end {
+                let (index, _) = best.unwrap();
+                let coord = self.unassigned.swap_remove(index);
+                let mut options: Vec<usize> = self.domains[coord].iter()
+                    .filter(|v| self.counts[**v].1 > 0)
+                    .cloned()
+                    .collect();
+                options.shuffle(rng);
+                self.filled[coord] = true;
+                self.stack.push(Frame { coord, index, options, next: 0, placed: None, ruled_out: Vec::new() });
+            }
+            if !self.advance(state, allowed, place, remove) {
+                return Status::NoSolution;
+            }
+        }
+    }
+
+    /// Try the next option for the last coordinate filled in, going back to earlier coordinates
+    /// when there are none left. Returns false if every option has been tried.
+    fn advance<S, A, P, R>(&mut self, state: &mut S, allowed: &A, place: &mut P, remove: &mut R) -> bool
+    where
+        A: Fn(&S, &hex_coordinates::Tile, &V) -> bool,
+        P: FnMut(&mut S, &hex_coordinates::Tile, &V),
+        R: FnMut(&mut S, &hex_coordinates::Tile)
+    {
+        loop {
+            self.take_back(state, remove);
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => return false,
+            };
+            let coord = &self.coords[frame.coord];
+            while frame.next < frame.options.len() {
+                let v = frame.options[frame.next];
+                frame.next += 1;
+                let value = match &self.counts[v].0 {
+                    // Constraints that look past the neighbors haven't been checked since the
+                    // options were worked out.
+                    Some(value) if !allowed(state, coord, value) => continue,
+                    Some(value) => value,
+                    None => {
+                        self.counts[v].1 -= 1;
+                        frame.placed = Some(v);
+                        return true;
+                    }
+                };
+                place(state, coord, value);
+                self.counts[v].1 -= 1;
+                frame.placed = Some(v);
+
+                for n in self.neighbors[frame.coord].iter() {
+                    if self.filled[*n] {
+                        continue;
+                    }
+                    let (counts, coords, ruled_out) = (&self.counts, &self.coords, &mut frame.ruled_out);
+                    self.domains[*n].retain(|w| {
+                        let keep = counts[*w].0.as_ref().map_or(true, |other| allowed(state, &coords[*n], other));
+                        if !keep {
+                            ruled_out.push((*n, *w));
+                        }
+                        keep
+                    });
+                }
+                return true;
+            }
+            self.pop();
+        }
+    }
+
+    /// Take out the value placed at the last coordinate filled in, if there is one, and put
+    /// back the options that it ruled out.
+    fn take_back<S, R>(&mut self, state: &mut S, remove: &mut R)
+    where
+        R: FnMut(&mut S, &hex_coordinates::Tile)
+    {
+        if let Some(frame) = self.stack.last_mut() {
+            if let Some(v) = frame.placed.take() {
+                if self.counts[v].0.is_some() {
+                    remove(state, &self.coords[frame.coord]);
+                }
+                self.counts[v].1 += 1;
+                for (n, w) in frame.ruled_out.drain(..) {
+                    let domain = &mut self.domains[n];
+                    let at = domain.binary_search(&w).unwrap_or_else(|at| at);
+                    domain.insert(at, w);
+                }
+            }
+        }
+    }
+
+    /// Take out everything placed, and start again in a new random order with twice as many
+    /// steps.
+    fn restart<S, R, N>(&mut self, rng: &mut N, state: &mut S, remove: &mut R)
+    where
+        R: FnMut(&mut S, &hex_coordinates::Tile),
+        N: Rng
+    {
+        while !self.stack.is_empty() {
+            self.take_back(state, remove);
+            self.pop();
+        }
+        self.restarts += 1;
+        self.try_steps *= 2;
+        self.steps_left = self.try_steps;
+        self.unassigned.shuffle(rng);
+    }
+
+    /// Put the last coordinate filled in back where it was in the unassigned list.
+    fn pop(&mut self) {
+        if let Some(frame) = self.stack.pop() {
+            self.filled[frame.coord] = false;
+            self.unassigned.push(frame.coord);
+            let last = self.unassigned.len() - 1;
+            self.unassigned.swap(frame.index, last);
+        }
+    }
+}
+
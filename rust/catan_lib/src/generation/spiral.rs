@@ -0,0 +1,85 @@
+//! The spiral that number tokens are laid along in the official rules: start at a corner of the
+//! board, go counterclockwise around the edge, and keep going around on the inside until every
+//! tile has been visited.
+
+use rand::prelude::*;
+use hexgrid::hex_coordinates;
+
+/// The six directions, in counterclockwise order starting from the east. Turning left is the
+/// next direction, and turning right is the one before.
+const DIRECTIONS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+/// Which way to try going next, in order: right, straight, left, sharp left, sharp right, back.
+/// Trying right first keeps the edge of the board (or the tiles already visited) on the right,
+/// which is what makes the path go counterclockwise and spiral inward.
+const TURNS: [usize; 6] = [5, 0, 1, 2, 4, 3];
+
+/// Order `tiles` along a spiral. The spiral starts at `start` if it is one of the tiles, or
+/// otherwise at a random corner. If the path gets stuck, for example because the board is made
+/// of separate islands, it carries on from a corner of the tiles that are left.
+pub(super) fn spiral<R: Rng>(
+    rng: &mut R,
+    tiles: &[hex_coordinates::Tile],
+    start: Option<hex_coordinates::Tile>
+) -> Vec<hex_coordinates::Tile> {
+    let mut remaining = tiles.to_vec();
+    // The input might come from a hash map, but the same seed should always give the same
+    // spiral.
+    remaining.sort_by_key(|c| (c.y, c.x));
+    remaining.dedup();
+    let mut order = Vec::with_capacity(remaining.len());
+
+    let mut current = match start.filter(|s| remaining.contains(s)) {
+        Some(start) => start,
+        None => match corners(&remaining).choose(rng) {
+            Some(corner) => *corner,
+            None => return order,
+        }
+    };
+    loop {
+        remaining.retain(|c| *c != current);
+        order.push(current);
+
+        let mut direction = first_direction(&remaining, &current);
+        loop {
+            let next = TURNS.iter()
+                .map(|turn| (direction + turn) % 6)
+                .find(|d| remaining.contains(&step(&current, *d)));
+            match next {
+                Some(d) => {
+                    direction = d;
+                    current = step(&current, d);
+                    remaining.retain(|c| *c != current);
+                    order.push(current);
+                },
+                None => break,
+            }
+        }
+
+        match corners(&remaining).first() {
+            Some(corner) => current = *corner,
+            None => return order,
+        }
+    }
+}
+
+fn step(tile: &hex_coordinates::Tile, direction: usize) -> hex_coordinates::Tile {
+    let (dx, dy) = DIRECTIONS[direction];
+    hex_coordinates::Tile::new(tile.x + dx, tile.y + dy)
+}
+
+/// The direction to leave a tile in so that the edge is on the right: towards a remaining tile,
+/// turning left from a direction that isn't.
+fn first_direction(remaining: &[hex_coordinates::Tile], tile: &hex_coordinates::Tile) -> usize {
+    let open = |d: usize| remaining.contains(&step(tile, d));
+    (0..6)
+        .find(|d| open(*d) && !open((d + 5) % 6))
+        .unwrap_or(0)
+}
+
+/// The tiles with the fewest neighbors, which are the corners of a regular board.
+fn corners(tiles: &[hex_coordinates::Tile]) -> Vec<hex_coordinates::Tile> {
+    let neighbors = |tile: &hex_coordinates::Tile| (0..6).filter(|d| tiles.contains(&step(tile, *d))).count();
+    let fewest = tiles.iter().map(neighbors).min().unwrap_or(0);
+    tiles.iter().filter(|t| neighbors(t) == fewest).cloned().collect()
+}
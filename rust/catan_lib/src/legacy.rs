@@ -0,0 +1,279 @@
+//! Importing games from the old Python server, so they can be carried on here.
+//!
+//! The Python server stored each board as the JSON from `GameBoard.asdict()`, which puts tiles,
+//! corners and edges together in one object, with string coordinates as keys:
+//!
+//! ```json
+//! {
+//!   "tiles": {
+//!     "0,0": {"number": 6, "resourcetype": "rocks", "thief": false, "facedown": false},
+//!     "0,0,Direction.CORNER_NE": {"player": 3, "type": "settlement"},
+//!     "0,0,Direction.EDGE_E": {"player": 3, "port": null}
+//!   }
+//! }
+//! ```
+//!
+//! The coordinates are the same axial coordinates used here, and the directions mean the same
+//! thing. Anything that can't be carried over, like an unknown field or a tile whose type was
+//! hidden from the player who saved it, is skipped and reported in `Imported::warnings`, rather
+//! than failing the whole import.
+
+use std::convert::TryFrom;
+use rand::SeedableRng;
+use serde_json::{self, Map, Value};
+use hexgrid::hex_coordinates::{self, CornerDirection, EdgeDirection};
+use super::{Game, GameGrid, GameID, GamePhase};
+use super::configuration::Rules;
+use super::rng::GameRng;
+use super::dice::Dice;
+use super::cities_and_knights;
+use super::scenarios;
+use super::player::{Player, PlayerID, DEFAULT_COLORS};
+use super::types::{Tile, TileType, Resource, Edge, Road, Corner, Settlement};
+
+/// Something that was imported, and everything that couldn't be.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Imported<T> {
+    pub value: T,
+    /// One message for each field, tile, corner or edge that was skipped.
+    pub warnings: Vec<String>,
+}
+
+/// Where a key in the board JSON points to.
+enum Location {
+    Tile(hex_coordinates::Tile),
+    Corner(hex_coordinates::Corner),
+    Edge(hex_coordinates::Edge),
+}
+
+/// Import a board from the JSON that the Python server stored. Only fails if the JSON isn't an
+/// object with a `tiles` object in it; everything else that is wrong is a warning.
+pub fn import_board(json: &str) -> Result<Imported<GameGrid>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid board: {}", e))?;
+    let board = value.as_object().ok_or("Invalid board: it should be an object")?;
+    let locations = board.get("tiles")
+        .and_then(Value::as_object)
+        .ok_or("Invalid board: it has no tiles")?;
+
+    let mut warnings: Vec<String> = board.keys()
+        .filter(|key| *key != "tiles")
+        .map(|key| format!("Skipped the unknown field '{}' on the board", key))
+        .collect();
+    let mut grid = GameGrid::new();
+
+    // Sort the keys so that the warnings come out in the same order every time.
+    let mut keys: Vec<&String> = locations.keys().collect();
+    keys.sort();
+    for key in keys {
+        let data = match locations[key].as_object() {
+            Some(data) => data,
+            None => {
+                warnings.push(format!("Skipped {}: it should be an object", key));
+                continue;
+            }
+        };
+        let result = match parse_location(key) {
+            Ok(Location::Tile(coords)) => import_tile(data).map(|tile| {
+                grid.tiles.insert(coords, tile);
+            }),
+            Ok(Location::Corner(coords)) => import_corner(data).map(|corner| {
+                if corner.settlement != Settlement::None {
+                    grid.corners.insert(coords, corner);
+                }
+            }),
+            Ok(Location::Edge(coords)) => import_edge(data).map(|edge| {
+                if edge.road != Road::None {
+                    grid.edges.insert(coords, edge);
+                }
+            }),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warnings.push(format!("Skipped {}: {}", key, e));
+        }
+        warnings.extend(unknown_fields(key, data));
+    }
+
+    Ok(Imported { value: grid, warnings })
+}
+
+/// Import a whole game. The Python server kept the players and the game ID in separate database
+/// columns from the board, so they are passed in here: `players` are the player IDs in turn
+/// order. The Python server kept names with accounts rather than players, so each player is named
+/// after their ID, and gets a color in turn order. The pieces they have already built are
+/// taken out of the pieces they have left.
+pub fn import_game(
+    id: GameID,
+    players: &[PlayerID],
+    board_json: &str,
+    rules: &Rules
+) -> Result<Imported<Game>, String> {
+    if players.len() > DEFAULT_COLORS.len() {
+        return Err(format!("Can't import a game with {} players, only up to {}", players.len(), DEFAULT_COLORS.len()));
+    }
+    let Imported { value: grid, mut warnings } = import_board(board_json)?;
+
+    let mut players: Vec<Player> = players.iter()
+        .zip(DEFAULT_COLORS.iter())
+        .map(|(id, color)| Player::new(*id, &format!("Player {}", id), *color, rules))
+        .collect();
+    for corner in grid.corners.values() {
+        let (owner, settlements, cities) = match corner.settlement {
+            Settlement::Settlement(owner) => (owner, 1, 0),
+            Settlement::City(owner) => (owner, 0, 1),
+            Settlement::None => continue,
+        };
+        match players.iter_mut().find(|p| p.id() == owner) {
+            Some(player) => {
+                player.settlements = player.settlements.saturating_sub(settlements);
+                player.cities = player.cities.saturating_sub(cities);
+            },
+            None => warnings.push(format!("Player {} has buildings, but isn't in the game", owner)),
+        }
+    }
+    for edge in grid.edges.values() {
+        if let Road::Road(owner) = edge.road {
+            match players.iter_mut().find(|p| p.id() == owner) {
+                Some(player) => player.roads = player.roads.saturating_sub(1),
+                None => warnings.push(format!("Player {} has roads, but isn't in the game", owner)),
+            }
+        }
+    }
+    warnings.sort();
+    warnings.dedup();
+    let mut rng = GameRng::seed_from_u64(id);
+    let cities_and_knights = cities_and_knights::new_state(rules, &mut rng);
+
+    let mut game = Game {
+        id,
+        players,
+        rules: rules.clone(),
+        grid,
+        development_cards: vec![],
+        phase: GamePhase::Playing,
+        rng,
+        dice: Dice::new(),
+        events: vec![],
+        cities_and_knights,
+        scenario: None,
+    };
+    scenarios::setup(&mut game)?;
+    Ok(Imported { value: game, warnings })
+}
+
+/// Parse coordinates like `1,2`, `1,2,CORNER_N` or `1,2,Direction.EDGE_E`.
+fn parse_location(key: &str) -> Result<Location, String> {
+    let parts: Vec<&str> = key.split(',').map(str::trim).collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return Err("these aren't valid coordinates".to_string());
+    }
+    let x = parts[0].parse::<i32>().map_err(|_| format!("'{}' is not a valid integer", parts[0]))?;
+    let y = parts[1].parse::<i32>().map_err(|_| format!("'{}' is not a valid integer", parts[1]))?;
+    let direction = match parts.get(2) {
+        Some(direction) => direction.trim_start_matches("Direction."),
+        None => return Ok(Location::Tile(hex_coordinates::Tile::new(x, y))),
+    };
+
+    let corner = |dir| Ok(Location::Corner(hex_coordinates::Corner::new(x, y, dir)));
+    let edge = |dir| Ok(Location::Edge(hex_coordinates::Edge::new(x, y, dir)));
+    match direction {
+        "CORNER_NE" => corner(CornerDirection::Northeast),
+        "CORNER_N" => corner(CornerDirection::North),
+        "CORNER_NW" => corner(CornerDirection::Northwest),
+        "CORNER_SW" => corner(CornerDirection::Southwest),
+        "CORNER_S" => corner(CornerDirection::South),
+        "CORNER_SE" => corner(CornerDirection::Southeast),
+        "EDGE_NE" => edge(EdgeDirection::Northeast),
+        "EDGE_NW" => edge(EdgeDirection::Northwest),
+        "EDGE_W" => edge(EdgeDirection::West),
+        "EDGE_SW" => edge(EdgeDirection::Southwest),
+        "EDGE_SE" => edge(EdgeDirection::Southeast),
+        "EDGE_E" => edge(EdgeDirection::East),
+        _ => Err(format!("'{}' is not a direction", direction)),
+    }
+}
+
+fn import_tile(data: &Map<String, Value>) -> Result<Tile, String> {
+    let facedown = optional_bool(data, "facedown")?;
+    let tile_type = match data.get("resourcetype") {
+        Some(Value::String(name)) => match name.to_lowercase().as_str() {
+            "wheat" => TileType::Resource(Resource::Wheat),
+            "sheep" => TileType::Resource(Resource::Sheep),
+            "rocks" => TileType::Resource(Resource::Stone),
+            "clay" => TileType::Resource(Resource::Clay),
+            "wood" => TileType::Resource(Resource::Wood),
+            "gold" => TileType::Resource(Resource::Gold),
+            "desert" => TileType::Desert,
+            "ocean" => TileType::Ocean,
+            _ => return Err(format!("'{}' is not a tile type", name)),
+        },
+        None | Some(Value::Null) if facedown => {
+            return Err("the tile is face down, and its type was hidden when it was saved".to_string());
+        },
+        None | Some(Value::Null) => return Err("the tile has no type".to_string()),
+        Some(other) => return Err(format!("{} is not a tile type", other)),
+    };
+    let number = match data.get("number") {
+        None | Some(Value::Null) => None,
+        Some(number) => Some(
+            number.as_i64()
+                .and_then(|n| i32::try_from(n).ok())
+                .ok_or(format!("{} is not a valid number", number))?
+        ),
+    };
+    Ok(Tile { tile_type, number, thief: optional_bool(data, "thief")?, faceup: !facedown })
+}
+
+fn import_corner(data: &Map<String, Value>) -> Result<Corner, String> {
+    let player = optional_player(data)?;
+    let settlement = match (data.get("type").and_then(Value::as_str), player) {
+        (Some("settlement"), Some(player)) => Settlement::Settlement(player),
+        (Some("city"), Some(player)) => Settlement::City(player),
+        (Some(kind @ ("settlement" | "city")), None) => return Err(format!("the {} has no player", kind)),
+        (Some(kind), _) => return Err(format!("'{}' is not a kind of building", kind)),
+        (None, Some(_)) => return Err("the corner has a player, but no building".to_string()),
+        (None, None) => match data.get("type") {
+            None | Some(Value::Null) => Settlement::None,
+            Some(other) => return Err(format!("{} is not a kind of building", other)),
+        },
+    };
+    Ok(Corner { settlement, knight: None })
+}
+
+fn import_edge(data: &Map<String, Value>) -> Result<Edge, String> {
+    // The Python server never decided how to store ports, so there is nothing to convert them to.
+    if !data.get("port").map_or(true, Value::is_null) {
+        return Err("ports can't be imported".to_string());
+    }
+    let road = match optional_player(data)? {
+        Some(player) => Road::Road(player),
+        None => Road::None,
+    };
+    Ok(Edge { port: None, road, shoal: None })
+}
+
+fn optional_bool(data: &Map<String, Value>, field: &str) -> Result<bool, String> {
+    match data.get(field) {
+        None | Some(Value::Null) => Ok(false),
+        Some(Value::Bool(b)) => Ok(*b),
+        Some(other) => Err(format!("'{}' should be true or false, not {}", field, other)),
+    }
+}
+
+fn optional_player(data: &Map<String, Value>) -> Result<Option<PlayerID>, String> {
+    match data.get("player") {
+        None | Some(Value::Null) => Ok(None),
+        Some(player) => player.as_u64()
+            .map(Some)
+            .ok_or(format!("{} is not a player ID", player)),
+    }
+}
+
+/// Warnings for the fields in `data` that the importer doesn't know about.
+fn unknown_fields(key: &str, data: &Map<String, Value>) -> Vec<String> {
+    const KNOWN: [&str; 7] = ["number", "resourcetype", "thief", "facedown", "player", "type", "port"];
+    data.keys()
+        .filter(|field| !KNOWN.contains(&field.as_str()))
+        .map(|field| format!("Skipped the unknown field '{}' at {}", field, key))
+        .collect()
+}
@@ -2,24 +2,57 @@ pub mod types;
 pub mod player;
 pub mod configuration;
 pub mod generation;
+pub mod analysis;
+pub mod map_string;
+pub mod save;
+pub mod legacy;
+pub mod builder;
+pub mod rng;
+pub mod dice;
+pub mod events;
+pub mod cities_and_knights;
+pub mod scenarios;
+pub mod render;
 
 use hexgrid::{HexGrid, hex_coordinates};
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use hexgrid::hex_coordinates::HexCoord;
 use rand;
+use rand::SeedableRng;
 use rand::seq::SliceRandom;
 
 pub type GameID = u64;
 pub type GameGrid = HexGrid<types::Tile, types::Edge, types::Corner>;
 
+/// Where a game is up to.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum GamePhase {
+    /// Players are placing their first settlements and roads.
+    Setup,
+    /// Players are taking turns to roll the dice, trade and build.
+    Playing,
+    /// Someone has won.
+    Finished
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub id: GameID,
     pub players: Vec<player::Player>,
     pub rules: configuration::Rules,
     pub grid: GameGrid,
-    pub development_cards: Vec<types::DevelopmentCard>
+    pub development_cards: Vec<types::DevelopmentCard>,
+    pub phase: GamePhase,
+    pub rng: rng::GameRng,
+    pub dice: dice::Dice,
+    pub events: Vec<events::Event>,
+    /// Everything about the game that only matters in Cities & Knights, or `None` when playing
+    /// without it.
+    pub cities_and_knights: Option<cities_and_knights::State>,
+    /// Everything about the game that only matters in its scenario, or `None` when it isn't
+    /// playing one.
+    pub scenario: Option<scenarios::ScenarioState>,
 }
 
 impl Game {
@@ -27,6 +60,80 @@ impl Game {
         self.players.iter().map(|player| (player.id(), player.color.clone())).collect()
     }
 
+    /// Roll the dice for `player`, following `Rules::dice`, and record the roll in the player's
+    /// stats and in the event log.
+    pub fn roll_dice(&mut self, player: player::PlayerID) -> [u32; 2] {
+        let dice = self.dice.roll(&self.rules.dice, &mut self.rng);
+        if let Some(p) = self.players.iter_mut().find(|p| p.id() == player) {
+            p.record_roll(dice[0] + dice[1]);
+        }
+        self.events.push(events::Event::Roll { player, dice });
+        dice
+    }
+
+    /// What each player collects when `total` is rolled: one card for each settlement and two
+    /// for each city next to a tile with that number, unless the thief is on it, or it is face
+    /// down and `Rules::hide_unexplored_tiles` is set. In Cities & Knights, a city next to a
+    /// forest, pasture or mountain collects one resource and one commodity instead of two
+    /// resources.
+    pub fn production(&self, total: u32) -> BTreeMap<player::PlayerID, player::Resources> {
+        let commodities = self.rules.cities_and_knights.is_some();
+        let hidden = |tile: &types::Tile| self.rules.hide_unexplored_tiles && !tile.faceup;
+        let mut production: BTreeMap<player::PlayerID, player::Resources> = BTreeMap::new();
+        for (coords, tile) in self.grid.tiles.iter() {
+            let resource = match tile.tile_type {
+                types::TileType::Resource(resource) if tile.number == Some(total as i32) && !tile.thief && !hidden(tile) => resource,
+                _ => continue,
+            };
+            for corner in coords.get_corner_neighbors() {
+                let (owner, city) = match self.grid.corners.get(&corner).map(|c| c.settlement) {
+                    Some(types::Settlement::Settlement(owner)) => (owner, false),
+                    Some(types::Settlement::City(owner)) => (owner, true),
+                    _ => continue,
+                };
+                let collected = production.entry(owner).or_default();
+                collected[resource] += 1;
+                match resource.commodity() {
+                    Some(commodity) if city && commodities => collected[commodity] += 1,
+                    _ if city => collected[resource] += 1,
+                    _ => {},
+                }
+            }
+        }
+        production
+    }
+
+    /// Give every player what they collect when `total` is rolled. See `production`. The game's
+    /// scenario, if it has one, can give out more: see `scenarios::Scenario::collect`.
+    pub fn collect(&mut self, total: u32) -> BTreeMap<player::PlayerID, player::Resources> {
+        let production = self.production(total);
+        for player in self.players.iter_mut() {
+            if let Some(resources) = production.get(&player.id()) {
+                player.receive(resources);
+            }
+        }
+        scenarios::collect(self, total);
+        production
+    }
+
+    /// How many victory points `player` has that everyone can see: one for each settlement, two
+    /// for each city, and one for each victory point card they have shown, plus whatever Cities &
+    /// Knights and the game's scenario give them. See `cities_and_knights::bonus_victory_points`
+    /// and `scenarios::Scenario::victory_points`.
+    pub fn victory_points(&self, player: player::PlayerID) -> u32 {
+        let buildings: u32 = self.grid.corners.values()
+            .map(|c| match c.settlement {
+                types::Settlement::Settlement(owner) if owner == player => 1,
+                types::Settlement::City(owner) if owner == player => 2,
+                _ => 0,
+            })
+            .sum();
+        let cards = self.players.iter()
+            .find(|p| p.id() == player)
+            .map_or(0, |p| p.visible_devcards.iter().filter(|c| **c == types::DevelopmentCard::VictoryPoint).count() as u32);
+        buildings + cards + cities_and_knights::bonus_victory_points(self, player) + scenarios::victory_points(self, player)
+    }
+
     pub fn generate_demo() -> Game {
         let generation_config = configuration::MapGenerationSettings::defaults_vanilla();
         let rules = configuration::Rules::defaults_vanilla();
@@ -35,9 +142,9 @@ impl Game {
         generation::generate_numbers(&generation_config, &mut grid).unwrap();
 
         let players = vec![
-            player::Player::new(0, 255, &rules),
-            player::Player::new(1, 255 << 8, &rules),
-            player::Player::new(2, 255 << 16, &rules),
+            player::Player::new(0, "Blue", 255, &rules),
+            player::Player::new(1, "Green", 255 << 8, &rules),
+            player::Player::new(2, "Red", 255 << 16, &rules),
         ];
 
         let mut rng = rand::rngs::OsRng;
@@ -47,7 +154,8 @@ impl Game {
                 let player = players.choose(&mut rng).unwrap();
                 grid.edges.insert(edge_coords, types::Edge{
                     port: None,
-                    road: types::Road::Road(player.id())
+                    road: types::Road::Road(player.id()),
+                    shoal: None
                 });
             }
             
@@ -60,7 +168,8 @@ impl Game {
                 ].choose(&mut rng).unwrap();
                 
                 grid.corners.insert(corner_coords, types::Corner {
-                    settlement: corner_type
+                    settlement: corner_type,
+                    knight: None
                 });
             }
         }
@@ -70,7 +179,13 @@ impl Game {
             rules,
             grid,
             development_cards: vec![],
-            players
+            players,
+            phase: GamePhase::Playing,
+            rng: rng::GameRng::from_rng(&mut rng).unwrap(),
+            dice: dice::Dice::new(),
+            events: vec![],
+            cities_and_knights: None,
+            scenario: None
         }
     }
 }
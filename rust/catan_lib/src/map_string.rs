@@ -0,0 +1,209 @@
+//! A short text encoding of a map, for sharing boards in chat or in a URL.
+//!
+//! Only the board itself is encoded: tile types, numbers, the thief, whether tiles are face up,
+//! and ports. Roads and settlements are not included, since these are meant for sharing a map
+//! before a game starts. Fish shoals from the Fishermen of Catan scenario are left out too, so a
+//! map that is shared and then played with that scenario gets new shoals when the game is set up.
+//!
+//! The string only uses URL-safe characters. It looks like `1.2.0.5.<tiles>.<ports>`:
+//!
+//! * `1` is the format version. Decoding any other version fails.
+//! * `2.0` are the x and y coordinates of the lowest corner of the box containing every tile.
+//! * `5` is the width of that box.
+//! * `<tiles>` has two characters for every position in the box, row by row. The first is the
+//!   tile type, thief, and face-up state, and the second is the number (if any).
+//! * `<ports>` has six characters for every port: the edge's x, y, and direction, then the
+//!   resource, cost, and reward.
+//!
+//! Each character is a value from 0 to 63, using the URL-safe base64 alphabet.
+
+use std::convert::TryFrom;
+use hexgrid::{Grid, GridMut};
+use hexgrid::hex_coordinates::{self, CanonicalEdgeDir};
+use super::types::{Tile, TileType, Resource, Edge, Port, PortResource, Road};
+use super::GameGrid;
+
+pub const MAP_STRING_VERSION: &str = "1";
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Index 0 means there is no tile at that position.
+const TILE_TYPES: [Option<TileType>; 9] = [
+    None,
+    Some(TileType::Resource(Resource::Wheat)),
+    Some(TileType::Resource(Resource::Sheep)),
+    Some(TileType::Resource(Resource::Clay)),
+    Some(TileType::Resource(Resource::Stone)),
+    Some(TileType::Resource(Resource::Wood)),
+    Some(TileType::Resource(Resource::Gold)),
+    Some(TileType::Desert),
+    Some(TileType::Ocean),
+];
+
+const PORT_RESOURCES: [PortResource; 7] = [
+    PortResource::Any,
+    PortResource::One(Resource::Wheat),
+    PortResource::One(Resource::Sheep),
+    PortResource::One(Resource::Clay),
+    PortResource::One(Resource::Stone),
+    PortResource::One(Resource::Wood),
+    PortResource::One(Resource::Gold),
+];
+
+const EDGE_DIRS: [CanonicalEdgeDir; 3] = [
+    CanonicalEdgeDir::Northwest,
+    CanonicalEdgeDir::Northeast,
+    CanonicalEdgeDir::East,
+];
+
+/// Encode the board into a map string.
+///
+/// Fails if a value doesn't fit in one character, such as a number above 63 or a map more than
+/// 62 tiles wide.
+pub fn to_map_string<G>(grid: &G) -> Result<String, String>
+where
+    G: Grid<TileData = Tile, EdgeData = Edge>
+{
+    let coords: Vec<hex_coordinates::Tile> = grid.iter_tiles().map(|(c, _)| c).collect();
+    let min_x = coords.iter().map(|c| c.x).min().unwrap_or(0);
+    let max_x = coords.iter().map(|c| c.x).max().unwrap_or(0);
+    let min_y = coords.iter().map(|c| c.y).min().unwrap_or(0);
+    let max_y = coords.iter().map(|c| c.y).max().unwrap_or(-1);
+
+    let mut tiles = String::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            match grid.get_tile(&hex_coordinates::Tile::new(x, y)) {
+                Some(tile) => {
+                    let type_index = TILE_TYPES.iter()
+                        .position(|t| *t == Some(tile.tile_type))
+                        .unwrap();
+                    let flags = (tile.thief as usize) << 1 | tile.faceup as usize;
+                    tiles.push(encode_char(type_index * 4 + flags)?);
+                    tiles.push(match tile.number {
+                        None => encode_char(0)?,
+                        Some(n) if n > 0 => encode_char(n as usize)?,
+                        Some(n) => return Err(format!("Number {} can't be put in a map string", n)),
+                    });
+                },
+                None => tiles.push_str("AA"),
+            }
+        }
+    }
+
+    let mut ports: Vec<(hex_coordinates::Edge, Port)> = grid.iter_edges()
+        .filter_map(|(c, e)| e.port.map(|p| (c, p)))
+        .collect();
+    // Edges come out of the grid in no particular order, but the same map should always give
+    // the same string.
+    ports.sort_by_key(|(c, _)| (c.y, c.x, EDGE_DIRS.iter().position(|d| *d == c.dir)));
+
+    let mut port_str = String::new();
+    for (coords, port) in ports {
+        if coords.x < min_x - 1 || coords.y < min_y - 1 {
+            return Err(format!("Port at {} is too far from the tiles", coords));
+        }
+        port_str.push(encode_char((coords.x - min_x + 1) as usize)?);
+        port_str.push(encode_char((coords.y - min_y + 1) as usize)?);
+        port_str.push(encode_char(EDGE_DIRS.iter().position(|d| *d == coords.dir).unwrap())?);
+        port_str.push(encode_char(PORT_RESOURCES.iter().position(|r| *r == port.resource_type).unwrap())?);
+        if port.cost < 0 || port.reward < 0 {
+            return Err(format!("Port at {} has a negative cost or reward", coords));
+        }
+        port_str.push(encode_char(port.cost as usize)?);
+        port_str.push(encode_char(port.reward as usize)?);
+    }
+
+    Ok(format!("{}.{}.{}.{}.{}.{}", MAP_STRING_VERSION, min_x, min_y, max_x - min_x + 1, tiles, port_str))
+}
+
+/// Decode a map string created by `to_map_string`.
+pub fn from_map_string(s: &str) -> Result<GameGrid, String> {
+    let mut split = s.trim().split('.');
+    let mut next = |name: &str| split.next()
+        .ok_or_else(|| format!("Map string '{}' is missing the {}", s, name));
+
+    let version = next("version")?;
+    if version != MAP_STRING_VERSION {
+        return Err(format!("Map string version '{}' is not supported", version));
+    }
+    let min_x = parse_int(next("x coordinate")?)?;
+    let min_y = parse_int(next("y coordinate")?)?;
+    let width = parse_int(next("width")?)?;
+    let tiles = next("tiles")?.as_bytes();
+    let ports = next("ports")?.as_bytes();
+    if split.next().is_some() {
+        return Err(format!("Map string '{}' has too many parts", s));
+    }
+    if width <= 0 || tiles.len() % (2 * width as usize) != 0 {
+        return Err(format!("Map string tiles do not fit in a width of {}", width));
+    }
+    if ports.len() % 6 != 0 {
+        return Err("Map string ports are the wrong length".to_string());
+    }
+
+    let mut grid = GameGrid::new();
+    for (i, chunk) in tiles.chunks(2).enumerate() {
+        let i = i32::try_from(i).map_err(|_| "Map string has too many tiles".to_string())?;
+        let coords = hex_coordinates::Tile::new(offset(min_x, i % width)?, offset(min_y, i / width)?);
+        let code = decode_char(chunk[0])?;
+        let number = match decode_char(chunk[1])? {
+            0 => None,
+            n => Some(n as i32),
+        };
+        let tile_type = match TILE_TYPES.get(code / 4) {
+            Some(Some(tile_type)) => *tile_type,
+            Some(None) => continue,
+            None => return Err(format!("'{}' is not a valid tile", chunk[0] as char)),
+        };
+        grid.insert_tile(coords, Tile {
+            tile_type,
+            number,
+            thief: code & 2 != 0,
+            faceup: code & 1 != 0
+        });
+    }
+
+    for chunk in ports.chunks(6) {
+        let x = offset(min_x, decode_char(chunk[0])? as i32 - 1)?;
+        let y = offset(min_y, decode_char(chunk[1])? as i32 - 1)?;
+        let dir = *EDGE_DIRS.get(decode_char(chunk[2])?)
+            .ok_or_else(|| format!("'{}' is not a valid edge direction", chunk[2] as char))?;
+        let resource_type = *PORT_RESOURCES.get(decode_char(chunk[3])?)
+            .ok_or_else(|| format!("'{}' is not a valid port resource", chunk[3] as char))?;
+        grid.insert_edge(hex_coordinates::Edge { x, y, dir }, Edge {
+            port: Some(Port {
+                resource_type,
+                cost: decode_char(chunk[4])? as i32,
+                reward: decode_char(chunk[5])? as i32
+            }),
+            road: Road::None,
+            shoal: None
+        });
+    }
+
+    Ok(grid)
+}
+
+fn encode_char(value: usize) -> Result<char, String> {
+    ALPHABET.get(value)
+        .map(|c| *c as char)
+        .ok_or_else(|| format!("{} is too big to be put in a map string", value))
+}
+
+fn decode_char(c: u8) -> Result<usize, String> {
+    ALPHABET.iter()
+        .position(|a| *a == c)
+        .ok_or_else(|| format!("'{}' is not a valid character in a map string", c as char))
+}
+
+/// `base + by`, or an error if that is too big or too small to be a coordinate. The coordinates in
+/// a map string come from whoever wrote it, so they could be anything.
+fn offset(base: i32, by: i32) -> Result<i32, String> {
+    base.checked_add(by)
+        .ok_or_else(|| format!("Map string has a tile or port outside the board, {} away from {}", by, base))
+}
+
+fn parse_int(s: &str) -> Result<i32, String> {
+    s.parse::<i32>().or(Err(format!("'{}' is not a valid integer", s)))
+}
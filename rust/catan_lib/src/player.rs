@@ -1,39 +1,156 @@
 use serde::{Serialize, Deserialize, ser, de};
 use super::types;
+use super::types::Card;
 use super::configuration;
 use std::fmt;
 use std::str::FromStr;
+use std::collections::BTreeMap;
+use std::ops::{Add, AddAssign, Index, IndexMut};
 
 pub type PlayerID = u64;
 pub type PlayerColor = u32;
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+/// Colors for players who haven't picked one, in the order they are handed out.
+pub const DEFAULT_COLORS: [PlayerColor; 6] = [0xff0000, 0x0000ff, 0xffffff, 0xff8000, 0x008000, 0x804000];
+
+/// A bundle of resources and commodities, like a player's hand or the price of a road. Index it
+/// with a `types::Resource`, a `types::Commodity` or a `types::Card` to get how many there are.
+///
+/// In JSON, this is a map from each card to how many there are, like `{"Wood": 1, "Clay": 1}`.
+/// Cards that aren't listed count as 0.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(into = "BTreeMap<Card, u32>", from = "BTreeMap<Card, u32>")]
 pub struct Resources {
-    pub wheat: u32,
-    pub sheep: u32,
-    pub wood: u32,
-    pub clay: u32,
-    pub rocks: u32,
-    pub gold: u32,
+    counts: [u32; Card::ALL.len()],
 }
 
 impl Resources {
     pub fn new() -> Resources {
-        Resources {
-            wheat: 0,
-            sheep: 0,
-            wood: 0,
-            clay: 0,
-            rocks: 0,
-            gold: 0
+        Resources::default()
+    }
+
+    /// `count` of one card, and nothing else.
+    pub fn of<C: Into<Card>>(card: C, count: u32) -> Resources {
+        let mut resources = Resources::new();
+        resources[card] = count;
+        resources
+    }
+
+    /// Each card there is at least one of, and how many there are, in the order of `Card::ALL`.
+    pub fn iter(&self) -> impl Iterator<Item = (Card, u32)> + '_ {
+        Card::ALL.iter()
+            .map(move |card| (*card, self[*card]))
+            .filter(|(_, count)| *count > 0)
+    }
+
+    /// How many cards there are altogether, for example to check a hand against
+    /// `Rules::thief_resource_threshold`.
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    /// True if there are at least as many of every card as in `other`.
+    pub fn contains(&self, other: &Resources) -> bool {
+        other.iter().all(|(card, count)| self[card] >= count)
+    }
+
+    /// Take `other` away, or return `None` if there aren't enough of some card.
+    pub fn checked_sub(&self, other: &Resources) -> Option<Resources> {
+        let mut result = self.clone();
+        for (card, count) in other.iter() {
+            result[card] = result[card].checked_sub(count)?;
         }
+        Some(result)
+    }
+}
+
+impl<C: Into<Card>> Index<C> for Resources {
+    type Output = u32;
+
+    fn index(&self, card: C) -> &u32 {
+        &self.counts[card.into().index()]
     }
 }
 
+impl<C: Into<Card>> IndexMut<C> for Resources {
+    fn index_mut(&mut self, card: C) -> &mut u32 {
+        &mut self.counts[card.into().index()]
+    }
+}
+
+impl AddAssign<&Resources> for Resources {
+    fn add_assign(&mut self, other: &Resources) {
+        for (card, count) in other.iter() {
+            self[card] += count;
+        }
+    }
+}
+
+impl Add<&Resources> for Resources {
+    type Output = Resources;
+
+    fn add(mut self, other: &Resources) -> Resources {
+        self += other;
+        self
+    }
+}
+
+impl From<Resources> for BTreeMap<Card, u32> {
+    fn from(resources: Resources) -> BTreeMap<Card, u32> {
+        resources.iter().collect()
+    }
+}
+
+impl From<BTreeMap<Card, u32>> for Resources {
+    fn from(map: BTreeMap<Card, u32>) -> Resources {
+        let mut resources = Resources::new();
+        for (card, count) in map {
+            resources[card] = count;
+        }
+        resources
+    }
+}
+
+/// Whether a player is at the table right now. This isn't saved, since nobody is connected to a
+/// game that has just been loaded.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Connection {
+    Connected,
+    /// Connected, but hasn't done anything for a while.
+    Away,
+    #[default]
+    Disconnected,
+}
+
+/// What has happened to a player over the game, for showing at the end.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    /// Every resource and commodity the player was given, from rolls or anything else.
+    pub resources_received: Resources,
+    /// Cards this player stole from other players.
+    pub cards_stolen: u32,
+    /// Cards other players stole from this player.
+    pub cards_lost: u32,
+    /// How many times this player rolled each total, so `dice_rolls[7]` is how many 7s they
+    /// rolled. The first two are always 0.
+    pub dice_rolls: [u32; 13],
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Player {
     id: u64,
+    pub name: String,
+    /// The account of the person playing, on whatever server is running the game.
+    pub account: Option<String>,
     pub color: PlayerColor,
+    /// The name of the bot that plays for this player, or `None` if a person is playing.
+    pub bot: Option<String>,
+    #[serde(skip)]
+    pub connection: Connection,
     pub hidden_devcards: Vec<types::DevelopmentCard>,
     pub visible_devcards: Vec<types::DevelopmentCard>,
     pub resources: Resources,
@@ -41,23 +158,56 @@ pub struct Player {
     pub ships: u32,
     pub settlements: u32,
     pub cities: u32,
+    pub stats: PlayerStats,
 }
 
 impl Player {
-    pub fn new(id: u64, color: PlayerColor, config: &configuration::Rules) -> Player {
+    pub fn new(id: u64, name: &str, color: PlayerColor, config: &configuration::Rules) -> Player {
         Player {
             id,
+            name: name.to_string(),
+            account: None,
             color,
+            bot: None,
+            connection: Connection::Disconnected,
             hidden_devcards: Vec::new(),
             visible_devcards: Vec::new(),
             resources: Resources::new(),
             roads: config.road_count,
             ships: config.ship_count,
             settlements: config.settlement_count,
-            cities: config.city_count
+            cities: config.city_count,
+            stats: PlayerStats::default(),
         }
     }
 
+    /// Give this player some cards, and count them in `stats`.
+    pub fn receive(&mut self, resources: &Resources) {
+        self.resources += resources;
+        self.stats.resources_received += resources;
+    }
+
+    /// Count a roll of the dice by this player in `stats`. Totals that two dice can't roll are
+    /// ignored.
+    pub fn record_roll(&mut self, total: u32) {
+        if (2..=12).contains(&total) {
+            self.stats.dice_rolls[total as usize] += 1;
+        }
+    }
+
+    /// Take one `card` from `victim`, and count it in both players' `stats`. Fails if the victim
+    /// doesn't have that card.
+    pub fn steal<C: Into<Card>>(&mut self, victim: &mut Player, card: C) -> Result<(), String> {
+        let card = card.into();
+        let taken = victim.resources.checked_sub(&Resources::of(card, 1))
+            .ok_or_else(|| format!("{} has no {:?} to steal", victim.name, card))?;
+        victim.resources = taken;
+        victim.stats.cards_lost += 1;
+        self.resources[card] += 1;
+        self.stats.cards_stolen += 1;
+        Ok(())
+    }
+
     pub fn id(&self) -> PlayerID {
         self.id
     }
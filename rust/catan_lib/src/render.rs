@@ -0,0 +1,68 @@
+//! Text rendering for the game types, so that a `GameGrid` can be drawn with
+//! `hexgrid::render::render_text`.
+//!
+//! Tiles show a two letter resource name, the number, and a `*` if the thief is there. Each
+//! player gets their own glyph, based on their ID:
+//!
+//! * Roads are digits (`0` to `9`), and ships are circled digits (`⓪` to `⑨`).
+//! * Settlements are lowercase letters (`a` to `z`), and cities are uppercase letters.
+//! * Ports with no road on them are `@`.
+//!
+//! Players with IDs too big to have a glyph are drawn as `?`.
+
+use hexgrid::render::Glyph;
+use super::player::PlayerID;
+use super::types::{Tile, TileType, Resource, Edge, Road, Corner, Settlement};
+
+const SHIP_GLYPHS: [char; 10] = ['⓪', '①', '②', '③', '④', '⑤', '⑥', '⑦', '⑧', '⑨'];
+
+impl Glyph for Tile {
+    fn glyph(&self) -> String {
+        let name = match self.tile_type {
+            TileType::Resource(Resource::Wheat) => "Wh",
+            TileType::Resource(Resource::Sheep) => "Sh",
+            TileType::Resource(Resource::Clay) => "Cl",
+            TileType::Resource(Resource::Stone) => "St",
+            TileType::Resource(Resource::Wood) => "Wo",
+            TileType::Resource(Resource::Gold) => "Go",
+            TileType::Desert => "De",
+            TileType::Ocean => "~~",
+        };
+        let number = self.number.map(|n| format!(" {}", n)).unwrap_or_default();
+        let thief = if self.thief { "*" } else { "" };
+        format!("{}{}{}", name, number, thief)
+    }
+}
+
+impl Glyph for Edge {
+    fn glyph(&self) -> String {
+        match (self.road, self.port) {
+            (Road::Road(id), _) => player_glyph(id, |id| std::char::from_digit(id as u32, 10)),
+            (Road::Ship(id), _) => player_glyph(id, |id| SHIP_GLYPHS.get(id as usize).cloned()),
+            (Road::None, Some(_)) => "@".to_string(),
+            (Road::None, None) => String::new(),
+        }
+    }
+}
+
+impl Glyph for Corner {
+    fn glyph(&self) -> String {
+        match self.settlement {
+            Settlement::Settlement(id) => player_glyph(id, |id| letter(b'a', id)),
+            Settlement::City(id) => player_glyph(id, |id| letter(b'A', id)),
+            Settlement::None => String::new(),
+        }
+    }
+}
+
+fn player_glyph<F: Fn(PlayerID) -> Option<char>>(id: PlayerID, glyph: F) -> String {
+    glyph(id).unwrap_or('?').to_string()
+}
+
+fn letter(first: u8, id: PlayerID) -> Option<char> {
+    if id < 26 {
+        Some((first + id as u8) as char)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,53 @@
+//! The random number generator that a game uses once it has started, for things like rolling the
+//! dice. It is part of the `Game`, so that a saved game carries on with the same rolls it would
+//! have had if it hadn't been saved, and a game started from a seed can be replayed exactly.
+//! Seeded map generation uses it too, so that a seed always gives the same map.
+//!
+//! This is SplitMix64: not good enough for cryptography, but plenty for dice, and its whole state
+//! is one number, so it is easy to save.
+
+use rand::{RngCore, SeedableRng, Error};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for GameRng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> GameRng {
+        GameRng { state: u64::from_le_bytes(seed) }
+    }
+
+    fn seed_from_u64(seed: u64) -> GameRng {
+        GameRng { state: seed }
+    }
+}
@@ -0,0 +1,188 @@
+//! Saving and loading games, so that games saved by older versions can still be loaded.
+//!
+//! A save is JSON that looks like `{"version": 2, "game": {...}}`, where `game` is the `Game`
+//! as serde writes it. Saves from before there was a version number are just the game on its
+//! own, and count as version 1.
+//!
+//! Loading a save from an older version runs it through every migration since then, one version
+//! at a time, before turning it into a `Game`. So when a change to `Game`, or to anything inside
+//! it, changes how it is saved:
+//!
+//! 1. Add one to `SAVE_VERSION`.
+//! 2. Add a function to `MIGRATIONS` that turns a game saved by the previous version into one
+//!    that the new version can load.
+//! 3. Add a save from the previous version to `tests/fixtures`, and a test that loads it.
+
+use serde::{Serialize, Deserialize};
+use serde_json::{self, Map, Value};
+use super::Game;
+
+pub const SAVE_VERSION: u32 = 8;
+
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// `MIGRATIONS[0]` turns a version 1 game into version 2, `MIGRATIONS[1]` turns version 2 into
+/// version 3, and so on.
+const MIGRATIONS: [Migration; SAVE_VERSION as usize - 1] = [
+    v1_add_victory_points,
+    v2_resources_as_maps,
+    v3_add_phase_and_names,
+    v4_add_player_details,
+    v5_add_dice,
+    v6_add_cities_and_knights,
+    v7_add_scenario,
+];
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    game: Value,
+}
+
+/// Save a game as JSON, with the current version number.
+pub fn to_json(game: &Game) -> Result<String, String> {
+    let game = serde_json::to_value(game).map_err(|e| format!("Couldn't save the game: {}", e))?;
+    serde_json::to_string(&SaveFile { version: SAVE_VERSION, game })
+        .map_err(|e| format!("Couldn't save the game: {}", e))
+}
+
+/// Load a game saved by this or any older version. Fails if the save is from a newer version.
+pub fn from_json(json: &str) -> Result<Game, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid save: {}", e))?;
+    let (version, mut game) = if value.get("version").is_some() {
+        let save: SaveFile = serde_json::from_value(value).map_err(|e| format!("Invalid save: {}", e))?;
+        (save.version, save.game)
+    } else {
+        (1, value)
+    };
+
+    if version == 0 || version > SAVE_VERSION {
+        return Err(format!(
+            "This save is from version {}, but only versions 1 to {} can be loaded",
+            version, SAVE_VERSION
+        ));
+    }
+    for migration in MIGRATIONS[version as usize - 1..].iter() {
+        migration(&mut game)?;
+    }
+
+    serde_json::from_value(game).map_err(|e| format!("Invalid save from version {}: {}", version, e))
+}
+
+/// Version 2 added `Rules::victory_points`. Every game before then was played to 10.
+fn v1_add_victory_points(game: &mut Value) -> Result<(), String> {
+    let rules = game.get_mut("rules")
+        .and_then(Value::as_object_mut)
+        .ok_or("Invalid save: the game has no rules")?;
+    rules.entry("victory_points").or_insert(Value::from(10));
+    Ok(())
+}
+
+/// Version 3 changed `Resources` from a struct with a field for each resource to a map from
+/// `Card` to how many there are, so it could hold commodities too. Both the players' hands and the
+/// prices in the rules are `Resources`.
+fn v2_resources_as_maps(game: &mut Value) -> Result<(), String> {
+    let players = game.get_mut("players")
+        .and_then(Value::as_array_mut)
+        .ok_or("Invalid save: the game has no players")?;
+    for player in players {
+        if let Some(resources) = player.get_mut("resources") {
+            *resources = resources_as_map(resources)?;
+        }
+    }
+
+    // Costs were added shortly before this version, so older saves might not have them.
+    if let Some(costs) = game.pointer_mut("/rules/costs").and_then(Value::as_object_mut) {
+        for price in costs.values_mut() {
+            *price = resources_as_map(price)?;
+        }
+    }
+    Ok(())
+}
+
+fn resources_as_map(resources: &Value) -> Result<Value, String> {
+    let fields = resources.as_object().ok_or("Invalid save: resources should be an object")?;
+    let mut map = Map::new();
+    for (field, count) in fields {
+        let card = match field.as_str() {
+            "wheat" => "Wheat",
+            "sheep" => "Sheep",
+            "wood" => "Wood",
+            "clay" => "Clay",
+            "rocks" => "Stone",
+            "gold" => "Gold",
+            _ => return Err(format!("Invalid save: '{}' is not a resource", field)),
+        };
+        if count.as_u64() != Some(0) {
+            map.insert(card.to_string(), count.clone());
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+/// Version 4 added `Game::phase`, and `Player::name` and `Player::bot`. Every game saved before
+/// then was already being played, by people, and players are named after their seat.
+fn v3_add_phase_and_names(game: &mut Value) -> Result<(), String> {
+    let fields = game.as_object_mut().ok_or("Invalid save: the game should be an object")?;
+    fields.entry("phase").or_insert(Value::from("Playing"));
+    let players = fields.get_mut("players")
+        .and_then(Value::as_array_mut)
+        .ok_or("Invalid save: the game has no players")?;
+    for (i, player) in players.iter_mut().enumerate() {
+        let player = player.as_object_mut().ok_or("Invalid save: a player should be an object")?;
+        player.entry("name").or_insert(Value::from(format!("Player {}", i + 1)));
+        player.entry("bot").or_insert(Value::from(false));
+    }
+    Ok(())
+}
+
+/// Version 5 added `Player::account` and `Player::stats`, and changed `Player::bot` to say which
+/// bot is playing. Version 4 didn't say, so those players get the bot called `default`. Nothing was
+/// counted for the stats before then, so they start at 0.
+fn v4_add_player_details(game: &mut Value) -> Result<(), String> {
+    let players = game.get_mut("players")
+        .and_then(Value::as_array_mut)
+        .ok_or("Invalid save: the game has no players")?;
+    for player in players {
+        let player = player.as_object_mut().ok_or("Invalid save: a player should be an object")?;
+        player.entry("account").or_insert(Value::Null);
+        let bot = match player.get("bot") {
+            Some(Value::Bool(true)) => Value::from("default"),
+            _ => Value::Null,
+        };
+        player.insert("bot".to_string(), bot);
+        player.entry("stats").or_insert(serde_json::json!({
+            "resources_received": {},
+            "cards_stolen": 0,
+            "cards_lost": 0,
+            "dice_rolls": vec![0; 13],
+        }));
+    }
+    Ok(())
+}
+
+/// Version 6 added `Game::rng`, `Game::dice` and `Game::events`. The random number generator
+/// starts from the game's ID, so that loading the same old save always gives the same rolls.
+fn v5_add_dice(game: &mut Value) -> Result<(), String> {
+    let id = game.get("id").and_then(Value::as_u64).ok_or("Invalid save: the game has no ID")?;
+    let fields = game.as_object_mut().ok_or("Invalid save: the game should be an object")?;
+    fields.entry("rng").or_insert(serde_json::json!({"state": id}));
+    fields.entry("dice").or_insert(serde_json::json!({"deck": [], "recent": []}));
+    fields.entry("events").or_insert(serde_json::json!([]));
+    Ok(())
+}
+
+/// Version 7 added `Game::cities_and_knights`. Nothing could be saved with Cities & Knights
+/// before then, so older games never use it.
+fn v6_add_cities_and_knights(game: &mut Value) -> Result<(), String> {
+    let fields = game.as_object_mut().ok_or("Invalid save: the game should be an object")?;
+    fields.entry("cities_and_knights").or_insert(Value::Null);
+    Ok(())
+}
+
+/// Version 8 added `Game::scenario`. Older games never play a scenario.
+fn v7_add_scenario(game: &mut Value) -> Result<(), String> {
+    let fields = game.as_object_mut().ok_or("Invalid save: the game should be an object")?;
+    fields.entry("scenario").or_insert(Value::Null);
+    Ok(())
+}
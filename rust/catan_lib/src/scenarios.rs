@@ -0,0 +1,92 @@
+//! Scenarios, which add their own pieces and rules on top of the base game, like the ones in
+//! Traders & Barbarians. A game plays a scenario when `Rules::scenario` is set, and keeps what the
+//! scenario needs in `Game::scenario`.
+//!
+//! To add a scenario:
+//!
+//! 1. Write its settings as a struct, and implement `Scenario` for it.
+//! 2. Add a variant for the settings to `ScenarioRules`, and one for what it keeps during a game
+//!    to `ScenarioState`.
+//! 3. Add the new variant to `ScenarioRules::scenario`.
+//!
+//! The game calls the `Scenario` methods at the right times, and anything else that the scenario
+//! lets players do goes in functions in its own module, like `fishermen::exchange`.
+
+use serde::{Serialize, Deserialize};
+use super::Game;
+use super::player::PlayerID;
+
+pub mod fishermen;
+
+/// What a scenario adds to the game. See the module documentation.
+pub trait Scenario {
+    /// Get a new game ready: put the scenario's pieces on the board, and set `Game::scenario`.
+    /// Fails if the board doesn't have room for them.
+    fn setup(&self, game: &mut Game) -> Result<(), String>;
+
+    /// Called after the players have collected their resources for a roll of `total`.
+    fn collect(&self, _game: &mut Game, _total: u32) {}
+
+    /// Victory points that the scenario gives `player`, on top of the base game's.
+    fn victory_points(&self, _game: &Game, _player: PlayerID) -> u32 {
+        0
+    }
+
+    /// How many victory points `player` needs to win.
+    fn victory_points_to_win(&self, game: &Game, _player: PlayerID) -> u32 {
+        game.rules.victory_points
+    }
+}
+
+/// The scenario a game is played with, and its settings.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ScenarioRules {
+    Fishermen(fishermen::FishermenRules),
+}
+
+impl ScenarioRules {
+    pub fn scenario(&self) -> &dyn Scenario {
+        match self {
+            ScenarioRules::Fishermen(rules) => rules,
+        }
+    }
+}
+
+/// Everything about a game that only matters in its scenario.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ScenarioState {
+    Fishermen(fishermen::State),
+}
+
+/// Set up the game's scenario, if it has one. See `Scenario::setup`.
+pub fn setup(game: &mut Game) -> Result<(), String> {
+    match game.rules.scenario.clone() {
+        Some(rules) => rules.scenario().setup(game),
+        None => Ok(()),
+    }
+}
+
+/// See `Scenario::collect`. `Game::collect` calls this.
+pub fn collect(game: &mut Game, total: u32) {
+    if let Some(rules) = game.rules.scenario.clone() {
+        rules.scenario().collect(game, total);
+    }
+}
+
+/// See `Scenario::victory_points`. `Game::victory_points` adds these in.
+pub fn victory_points(game: &Game, player: PlayerID) -> u32 {
+    match &game.rules.scenario {
+        Some(rules) => rules.scenario().victory_points(game, player),
+        None => 0,
+    }
+}
+
+/// How many victory points `player` needs to win, counting anything the scenario changes.
+pub fn victory_points_to_win(game: &Game, player: PlayerID) -> u32 {
+    match &game.rules.scenario {
+        Some(rules) => rules.scenario().victory_points_to_win(game, player),
+        None => game.rules.victory_points,
+    }
+}
@@ -0,0 +1,349 @@
+//! The Fishermen of Catan scenario, from Traders & Barbarians.
+//!
+//! Fish shoals sit along the coast, each with a number, like a tile. When a shoal's number is
+//! rolled, each settlement on either corner of its edge catches a fish token from the bag, and
+//! each city catches two. Tokens have one, two or three fish on them, and players spend them at
+//! the fish exchange (see `FishExchange`). Players who pay with more fish than something costs
+//! don't get any back.
+//!
+//! One token in the bag is the old boot. Whoever draws it needs one more victory point to win,
+//! until they give it to a player with at least as many points as them.
+
+use std::collections::BTreeMap;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+use hexgrid::hex_coordinates::{self, HexCoord};
+use super::{Scenario, ScenarioState};
+use super::super::{Game, GameGrid};
+use super::super::events::Event;
+use super::super::player::{PlayerID, Resources};
+use super::super::types::{Edge, FishShoal, FishToken, Resource, Road, Settlement, TileType};
+
+/// Settings for the Fishermen of Catan scenario.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FishermenRules {
+    /// The numbers on the shoals. There is one shoal for each number.
+    pub shoal_numbers: Vec<i32>,
+    pub one_fish_count: u32,
+    pub two_fish_count: u32,
+    pub three_fish_count: u32,
+}
+
+/// Everything about a game that only matters in Fishermen of Catan.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    /// The tokens left in the bag, with the next one to be drawn last.
+    pub bag: Vec<FishToken>,
+    /// The fish tokens that each player has, as the number of fish on each one.
+    pub fish: BTreeMap<PlayerID, Vec<u32>>,
+    /// The player who has the old boot, if anyone has drawn it.
+    pub old_boot: Option<PlayerID>,
+    /// Tokens spent at the fish exchange. They go back in the bag when it runs out.
+    pub spent: Vec<FishToken>,
+}
+
+impl State {
+    /// How many fish `player` has altogether.
+    pub fn fish(&self, player: PlayerID) -> u32 {
+        self.fish.get(&player).map_or(0, |tokens| tokens.iter().sum())
+    }
+}
+
+/// Something that players can get for their fish.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum FishExchange {
+    /// Take the thief off the board. The next player to roll a 7 puts it back.
+    RemoveThief,
+    /// Take a random resource from another player.
+    StealResource(PlayerID),
+    /// Take a resource from the bank.
+    TakeResource(Resource),
+    /// Build a road without paying for it.
+    BuildRoad(hex_coordinates::Edge),
+    /// Take the top development card.
+    DevelopmentCard,
+}
+
+impl FishExchange {
+    /// How many fish this costs.
+    pub fn cost(&self) -> u32 {
+        match self {
+            FishExchange::RemoveThief => 2,
+            FishExchange::StealResource(_) => 3,
+            FishExchange::TakeResource(_) => 4,
+            FishExchange::BuildRoad(_) => 5,
+            FishExchange::DevelopmentCard => 7,
+        }
+    }
+}
+
+impl Scenario for FishermenRules {
+    /// Put the shoals on the coast, unless the board already has them, and fill the bag.
+    fn setup(&self, game: &mut Game) -> Result<(), String> {
+        if !game.grid.edges.values().any(|e| e.shoal.is_some()) {
+            place_shoals(&mut game.grid, &self.shoal_numbers, &mut game.rng)?;
+        }
+        let mut bag = vec![FishToken::OldBoot];
+        for (fish, count) in [(1, self.one_fish_count), (2, self.two_fish_count), (3, self.three_fish_count)] {
+            bag.extend(std::iter::repeat(FishToken::Fish(fish)).take(count as usize));
+        }
+        bag.shuffle(&mut game.rng);
+        game.scenario = Some(ScenarioState::Fishermen(State {
+            bag,
+            fish: BTreeMap::new(),
+            old_boot: None,
+            spent: vec![],
+        }));
+        Ok(())
+    }
+
+    fn collect(&self, game: &mut Game, total: u32) {
+        let mut shoals: Vec<hex_coordinates::Edge> = game.grid.edges.iter()
+            .filter(|(_, e)| e.shoal.map_or(false, |s| s.number == total as i32))
+            .map(|(coords, _)| *coords)
+            .collect();
+        // Draw in the same order every time, so that a seeded game always plays the same.
+        shoals.sort_by_key(|e| (e.y, e.x, e.to_string()));
+        for shoal in shoals {
+            for corner in shoal.get_corner_neighbors() {
+                let (player, tokens) = match game.grid.corners.get(&corner).map(|c| c.settlement) {
+                    Some(Settlement::Settlement(player)) => (player, 1),
+                    Some(Settlement::City(player)) => (player, 2),
+                    _ => continue,
+                };
+                for _ in 0..tokens {
+                    draw(game, player);
+                }
+            }
+        }
+    }
+
+    /// The player with the old boot needs one more point than everyone else.
+    fn victory_points_to_win(&self, game: &Game, player: PlayerID) -> u32 {
+        let boot = state(game).ok().and_then(|s| s.old_boot) == Some(player);
+        game.rules.victory_points + boot as u32
+    }
+}
+
+fn state(game: &Game) -> Result<&State, String> {
+    match &game.scenario {
+        Some(ScenarioState::Fishermen(state)) => Ok(state),
+        _ => Err("This game isn't playing Fishermen of Catan".to_string()),
+    }
+}
+
+fn state_mut(game: &mut Game) -> Result<&mut State, String> {
+    match &mut game.scenario {
+        Some(ScenarioState::Fishermen(state)) => Ok(state),
+        _ => Err("This game isn't playing Fishermen of Catan".to_string()),
+    }
+}
+
+fn is_land(grid: &GameGrid, tile: &hex_coordinates::Tile) -> bool {
+    grid.tiles.get(tile).map_or(false, |t| t.tile_type != TileType::Ocean)
+}
+
+/// True if `edge` is between land and water. Anything off the edge of the board counts as water.
+pub fn is_coastal(grid: &GameGrid, edge: &hex_coordinates::Edge) -> bool {
+    let land = edge.get_tile_neighbors().iter().filter(|t| is_land(grid, t)).count();
+    land == 1
+}
+
+/// Put a shoal with each of `numbers` on a random coastal edge without a port. No two shoals
+/// share a corner, so that no settlement fishes at two of them.
+pub fn place_shoals<R: Rng>(grid: &mut GameGrid, numbers: &[i32], rng: &mut R) -> Result<(), String> {
+    let mut coast: Vec<hex_coordinates::Edge> = grid.tiles.keys()
+        .flat_map(|t| t.get_edge_neighbors())
+        .filter(|e| is_coastal(grid, e) && grid.edges.get(e).map_or(true, |e| e.port.is_none()))
+        .collect();
+    coast.sort_by_key(|e| (e.y, e.x, e.to_string()));
+    coast.dedup();
+    coast.shuffle(rng);
+
+    let mut chosen: Vec<hex_coordinates::Edge> = Vec::with_capacity(numbers.len());
+    for edge in coast {
+        if chosen.len() == numbers.len() {
+            break;
+        }
+        let corners = edge.get_corner_neighbors();
+        if !chosen.iter().any(|c| c.get_corner_neighbors().iter().any(|corner| corners.contains(corner))) {
+            chosen.push(edge);
+        }
+    }
+    if chosen.len() < numbers.len() {
+        return Err(format!("There is only room for {} fish shoals on the coast, not {}", chosen.len(), numbers.len()));
+    }
+
+    let mut numbers = numbers.to_vec();
+    numbers.shuffle(rng);
+    for (edge, number) in chosen.into_iter().zip(numbers) {
+        grid.edges.entry(edge)
+            .or_insert(Edge { port: None, road: Road::None, shoal: None })
+            .shoal = Some(FishShoal { number });
+    }
+    Ok(())
+}
+
+/// Draw a token from the bag for `player`. When the bag is empty, the spent tokens are shuffled
+/// back into it first.
+fn draw(game: &mut Game, player: PlayerID) {
+    let Game { scenario, rng, events, .. } = game;
+    let state = match scenario {
+        Some(ScenarioState::Fishermen(state)) => state,
+        _ => return,
+    };
+    if state.bag.is_empty() {
+        state.bag.append(&mut state.spent);
+        state.bag.shuffle(rng);
+    }
+    let token = match state.bag.pop() {
+        Some(token) => token,
+        None => return,
+    };
+    match token {
+        FishToken::Fish(fish) => state.fish.entry(player).or_default().push(fish),
+        FishToken::OldBoot => state.old_boot = Some(player),
+    }
+    events.push(Event::FishCaught { player, token });
+}
+
+/// The fish to spend on something that costs `cost`, from the tokens in `hand`. This wastes as
+/// few fish as it can, and then uses as few tokens as it can.
+fn choose_fish(hand: &[u32], cost: u32) -> Option<Vec<u32>> {
+    let count = |fish| hand.iter().filter(|f| **f == fish).count();
+    let mut best: Option<(u32, usize, Vec<u32>)> = None;
+    for ones in 0..=count(1) {
+        for twos in 0..=count(2) {
+            for threes in 0..=count(3) {
+                let total = (ones + 2 * twos + 3 * threes) as u32;
+                let tokens = ones + twos + threes;
+                if total < cost || best.as_ref().map_or(false, |(t, n, _)| (*t, *n) <= (total, tokens)) {
+                    continue;
+                }
+                let mut spend = vec![1; ones];
+                spend.extend(std::iter::repeat(2).take(twos));
+                spend.extend(std::iter::repeat(3).take(threes));
+                best = Some((total, tokens, spend));
+            }
+        }
+    }
+    best.map(|(_, _, spend)| spend)
+}
+
+/// True if `player` can build a road on `edge`: it has to be empty, on land, and next to one of
+/// their buildings, or to one of their roads without another player's building in the way.
+fn can_build_road(grid: &GameGrid, player: PlayerID, edge: &hex_coordinates::Edge) -> bool {
+    if grid.edges.get(edge).map_or(false, |e| e.road != Road::None) {
+        return false;
+    }
+    if !edge.get_tile_neighbors().iter().any(|t| is_land(grid, t)) {
+        return false;
+    }
+    edge.get_corner_neighbors().iter().any(|corner| {
+        match grid.corners.get(corner).map(|c| c.settlement) {
+            Some(Settlement::Settlement(owner)) | Some(Settlement::City(owner)) => owner == player,
+            _ => corner.get_edge_neighbors().iter()
+                .any(|e| grid.edges.get(e).map_or(false, |e| e.road == Road::Road(player))),
+        }
+    })
+}
+
+/// Spend `player`'s fish on something from the fish exchange. The fish are only spent if it can
+/// be done.
+pub fn exchange(game: &mut Game, player: PlayerID, exchange: FishExchange) -> Result<(), String> {
+    let hand = state(game)?.fish.get(&player).cloned().unwrap_or_default();
+    let spend = choose_fish(&hand, exchange.cost())
+        .ok_or_else(|| format!("That costs {} fish", exchange.cost()))?;
+    let seat = game.players.iter().position(|p| p.id() == player)
+        .ok_or_else(|| format!("There is no player {}", player))?;
+
+    match exchange {
+        FishExchange::RemoveThief => {
+            if !game.grid.tiles.values().any(|t| t.thief && t.tile_type != TileType::Ocean) {
+                return Err("The thief isn't on the board".to_string());
+            }
+        },
+        FishExchange::StealResource(victim) => {
+            let victim = game.players.iter().find(|p| p.id() == victim && p.id() != player)
+                .ok_or_else(|| format!("Player {} can't be stolen from", victim))?;
+            if victim.resources.is_empty() {
+                return Err(format!("{} has nothing to steal", victim.name));
+            }
+        },
+        FishExchange::TakeResource(_) => {},
+        FishExchange::BuildRoad(edge) => {
+            if game.players[seat].roads == 0 {
+                return Err("There are no roads left".to_string());
+            }
+            if !can_build_road(&game.grid, player, &edge) {
+                return Err(format!("A road can't go on {}", edge));
+            }
+        },
+        FishExchange::DevelopmentCard => {
+            if game.development_cards.is_empty() {
+                return Err("There are no development cards left".to_string());
+            }
+        },
+    }
+
+    let state = state_mut(game)?;
+    let tokens = state.fish.entry(player).or_default();
+    for fish in spend {
+        if let Some(i) = tokens.iter().position(|f| *f == fish) {
+            tokens.remove(i);
+        }
+        state.spent.push(FishToken::Fish(fish));
+    }
+
+    match exchange {
+        FishExchange::RemoveThief => {
+            for tile in game.grid.tiles.values_mut().filter(|t| t.tile_type != TileType::Ocean) {
+                tile.thief = false;
+            }
+        },
+        FishExchange::StealResource(victim) => {
+            let victim_seat = game.players.iter().position(|p| p.id() == victim).unwrap();
+            let cards: Vec<_> = game.players[victim_seat].resources.iter()
+                .flat_map(|(card, count)| std::iter::repeat(card).take(count as usize))
+                .collect();
+            let card = *cards.choose(&mut game.rng).unwrap();
+            let (thief, victim) = if seat < victim_seat {
+                let (left, right) = game.players.split_at_mut(victim_seat);
+                (&mut left[seat], &mut right[0])
+            } else {
+                let (left, right) = game.players.split_at_mut(seat);
+                (&mut right[0], &mut left[victim_seat])
+            };
+            thief.steal(victim, card)?;
+        },
+        FishExchange::TakeResource(resource) => game.players[seat].receive(&Resources::of(resource, 1)),
+        FishExchange::BuildRoad(edge) => {
+            game.grid.edges.entry(edge)
+                .or_insert(Edge { port: None, road: Road::None, shoal: None })
+                .road = Road::Road(player);
+            game.players[seat].roads -= 1;
+        },
+        FishExchange::DevelopmentCard => {
+            let card = game.development_cards.pop().unwrap();
+            game.players[seat].hidden_devcards.push(card);
+        },
+    }
+    game.events.push(Event::FishExchanged { player, exchange });
+    Ok(())
+}
+
+/// Give the old boot from `from` to `to`, who has to have at least as many victory points.
+pub fn give_old_boot(game: &mut Game, from: PlayerID, to: PlayerID) -> Result<(), String> {
+    if state(game)?.old_boot != Some(from) {
+        return Err(format!("Player {} doesn't have the old boot", from));
+    }
+    if from == to || !game.players.iter().any(|p| p.id() == to) {
+        return Err(format!("Player {} can't take the old boot", to));
+    }
+    if game.victory_points(to) < game.victory_points(from) {
+        return Err(format!("Player {} has fewer victory points than player {}", to, from));
+    }
+    state_mut(game)?.old_boot = Some(to);
+    Ok(())
+}
@@ -5,7 +5,7 @@ use serde::{Serialize, Deserialize};
 use super::player::{PlayerID};
 
 /// A type of resource, for use in building or trading.
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum Resource {
     Wheat,
     Sheep,
@@ -16,6 +16,88 @@ pub enum Resource {
     Gold
 }
 
+impl Resource {
+    pub const ALL: [Resource; 6] = [
+        Resource::Wheat,
+        Resource::Sheep,
+        Resource::Clay,
+        Resource::Stone,
+        Resource::Wood,
+        Resource::Gold,
+    ];
+
+    /// The commodity that a city on a tile of this resource collects in Cities & Knights, if
+    /// there is one.
+    pub fn commodity(self) -> Option<Commodity> {
+        match self {
+            Resource::Wood => Some(Commodity::Paper),
+            Resource::Sheep => Some(Commodity::Cloth),
+            Resource::Stone => Some(Commodity::Coin),
+            _ => None,
+        }
+    }
+}
+
+/// Commodities are used in Cities & Knights. Cities on pasture, forest and mountain tiles collect
+/// one of these instead of a second resource.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum Commodity {
+    Paper,
+    Cloth,
+    Coin
+}
+
+/// Anything that can be held in a hand of `player::Resources`: a resource, or a commodity.
+///
+/// In JSON, this is just the name of the resource or commodity, like `"Stone"` or `"Paper"`.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Card {
+    Resource(Resource),
+    Commodity(Commodity)
+}
+
+impl Card {
+    pub const ALL: [Card; 9] = [
+        Card::Resource(Resource::Wheat),
+        Card::Resource(Resource::Sheep),
+        Card::Resource(Resource::Clay),
+        Card::Resource(Resource::Stone),
+        Card::Resource(Resource::Wood),
+        Card::Resource(Resource::Gold),
+        Card::Commodity(Commodity::Paper),
+        Card::Commodity(Commodity::Cloth),
+        Card::Commodity(Commodity::Coin),
+    ];
+
+    /// Where this card is in `Card::ALL`.
+    pub fn index(self) -> usize {
+        match self {
+            Card::Resource(Resource::Wheat) => 0,
+            Card::Resource(Resource::Sheep) => 1,
+            Card::Resource(Resource::Clay) => 2,
+            Card::Resource(Resource::Stone) => 3,
+            Card::Resource(Resource::Wood) => 4,
+            Card::Resource(Resource::Gold) => 5,
+            Card::Commodity(Commodity::Paper) => 6,
+            Card::Commodity(Commodity::Cloth) => 7,
+            Card::Commodity(Commodity::Coin) => 8,
+        }
+    }
+}
+
+impl From<Resource> for Card {
+    fn from(resource: Resource) -> Card {
+        Card::Resource(resource)
+    }
+}
+
+impl From<Commodity> for Card {
+    fn from(commodity: Commodity) -> Card {
+        Card::Commodity(commodity)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Resource(Resource),
@@ -69,12 +151,32 @@ pub enum Road {
 pub struct Edge {
     /// Most edges don't have ports, so will be None
     pub port: Option<Port>,
-    pub road: Road
+    pub road: Road,
+    /// Fish shoals are used in the Fishermen of Catan scenario. They are on edges along the coast.
+    #[serde(default)]
+    pub shoal: Option<FishShoal>
 }
 
+/// A fishing ground in the Fishermen of Catan scenario. When its number is rolled, settlements and
+/// cities on either corner of its edge catch fish. See `scenarios::fishermen`.
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub struct FishShoal {
+    pub number: i32
+}
+
+/// A token drawn from the bag in the Fishermen of Catan scenario.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum FishToken {
+    /// One, two or three fish.
+    Fish(u32),
+    /// The old boot, which the player who draws it has to keep until they give it away.
+    OldBoot
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum Settlement {
     /// Nothing on this corner
+    #[default]
     None,
     /// Settlement (the small one)
     Settlement(PlayerID),
@@ -83,9 +185,54 @@ pub enum Settlement {
 }
 
 /// I made this a struct because I might add more fields in the future.
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
 pub struct Corner {
-    pub settlement: Settlement
+    pub settlement: Settlement,
+    /// Knights are used in Cities & Knights. A corner can have a knight or a settlement, but
+    /// not both.
+    #[serde(default)]
+    pub knight: Option<Knight>
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum KnightLevel {
+    Basic,
+    Strong,
+    Mighty
+}
+
+impl KnightLevel {
+    /// How much an active knight of this level adds to the defense against the barbarians, and
+    /// which knights it can displace.
+    pub fn strength(self) -> u32 {
+        match self {
+            KnightLevel::Basic => 1,
+            KnightLevel::Strong => 2,
+            KnightLevel::Mighty => 3,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub struct Knight {
+    pub owner: PlayerID,
+    pub level: KnightLevel,
+    /// Only active knights can move, displace other knights, or fight the barbarians.
+    pub active: bool
+}
+
+/// Something that a player can spend resources on. See `configuration::Costs` for the prices.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum Purchase {
+    Road,
+    Ship,
+    Settlement,
+    City,
+    DevelopmentCard,
+    /// A new basic knight, in Cities & Knights.
+    Knight,
+    ActivateKnight,
+    PromoteKnight
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
@@ -0,0 +1,88 @@
+#[cfg(test)]
+pub mod analysis_test {
+    use catan_lib::{GameGrid, analysis, configuration::MapGenerationSettings, generation};
+    use catan_lib::types::{Tile, TileType, Resource, Edge, Road, Port, PortResource};
+    use hexgrid::hex_coordinates::{self, CornerDirection, EdgeDirection};
+
+    fn tile(resource: Resource, number: i32) -> Tile {
+        Tile {
+            tile_type: TileType::Resource(resource),
+            number: Some(number),
+            thief: false,
+            faceup: true
+        }
+    }
+
+    fn three_tiles() -> GameGrid {
+        let mut grid = GameGrid::new();
+        grid.tiles.insert(hex_coordinates::Tile::new(0, 0), tile(Resource::Wheat, 6));
+        grid.tiles.insert(hex_coordinates::Tile::new(0, 1), tile(Resource::Sheep, 8));
+        grid.tiles.insert(hex_coordinates::Tile::new(1, 0), tile(Resource::Clay, 4));
+        grid.edges.insert(hex_coordinates::Edge::new(0, 0, EdgeDirection::East), Edge {
+            port: Some(Port { resource_type: PortResource::One(Resource::Wheat), cost: 2, reward: 1 }),
+            road: Road::None,
+            shoal: None
+        });
+        grid
+    }
+
+    #[test]
+    pub fn corner_score_test() {
+        let grid = three_tiles();
+        let middle = hex_coordinates::Corner::new(0, 0, CornerDirection::Northeast);
+        assert_eq!(analysis::corner_score(&grid, &middle), Some(5 + 5 + 3));
+
+        let edge = hex_coordinates::Corner::new(0, 0, CornerDirection::South);
+        assert_eq!(analysis::corner_score(&grid, &edge), None);
+    }
+
+    #[test]
+    pub fn report_test() {
+        let report = analysis::analyze(&three_tiles());
+
+        assert_eq!(report.resource_pips[&Resource::Wheat], 5);
+        assert_eq!(report.resource_pips[&Resource::Clay], 3);
+        assert_eq!(report.resource_tiles[&Resource::Sheep], 1);
+
+        let best = &report.best_spots(1)[0];
+        assert_eq!(best.corner, hex_coordinates::Corner::new(0, 0, CornerDirection::Northeast));
+        assert_eq!(best.pips, 13);
+        assert_eq!(best.production(), 13.0 / 36.0);
+        assert_eq!(best.resources.len(), 3);
+        assert_eq!(best.port.unwrap().cost, 2);
+        assert_eq!(report.worst_spots(1)[0].resources.len(), 1);
+
+        assert_eq!(report.scarcity()[0].0, Resource::Clay);
+
+        assert_eq!(report.ports.len(), 1);
+        assert_eq!(report.ports[0].spot_pips, 13);
+        assert_eq!(report.ports[0].resource_pips, Some(5));
+
+        assert!(report.balance_index > 0.0 && report.balance_index < 1.0);
+    }
+
+    #[test]
+    pub fn balance_index_test() {
+        let mut even = GameGrid::new();
+        even.tiles.insert(hex_coordinates::Tile::new(0, 0), tile(Resource::Wheat, 6));
+        even.tiles.insert(hex_coordinates::Tile::new(1, 0), tile(Resource::Wood, 8));
+        assert_eq!(analysis::analyze(&even).balance_index, 1.0);
+
+        let mut lopsided = even.clone();
+        lopsided.tiles.insert(hex_coordinates::Tile::new(2, 0), tile(Resource::Wood, 2));
+        assert!(analysis::analyze(&lopsided).balance_index < 1.0);
+    }
+
+    #[test]
+    pub fn generated_map_test() {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let mut grid = generation::generate_tiles(&config).unwrap();
+        generation::generate_numbers(&config, &mut grid).unwrap();
+        let report = analysis::analyze(&grid);
+
+        let total: i32 = config.numbers.iter().map(|n| analysis::pips(*n)).sum();
+        assert_eq!(report.resource_pips.values().sum::<i32>(), total);
+        assert!(report.spots.windows(2).all(|w| w[0].pips >= w[1].pips));
+        assert!(report.spots.iter().all(|s| s.pips <= config.max_corner_score));
+    }
+}
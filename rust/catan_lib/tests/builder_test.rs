@@ -0,0 +1,127 @@
+#[cfg(test)]
+pub mod builder_test {
+    use catan_lib::GamePhase;
+    use catan_lib::builder::{GameBuilder, PlayerSetup};
+    use catan_lib::configuration::Rules;
+    use catan_lib::configuration::registry::Registry;
+    use catan_lib::generation;
+    use catan_lib::player::{DEFAULT_COLORS, Resources};
+    use catan_lib::types::{Settlement, TileType};
+    use hexgrid::hex_coordinates::HexCoord;
+
+    fn four_players() -> GameBuilder {
+        GameBuilder::new()
+            .player(PlayerSetup::human("Alice").account("alice@example.com"))
+            .player(PlayerSetup::human("Bob").color(DEFAULT_COLORS[0]))
+            .player(PlayerSetup::bot("Carol", "easy"))
+            .player(PlayerSetup::human("Dave"))
+    }
+
+    #[test]
+    pub fn build_test() {
+        let game = four_players().id(99).seed(3).build().unwrap();
+        let rules = Rules::defaults_vanilla();
+
+        assert_eq!(game.id, 99);
+        assert_eq!(game.phase, GamePhase::Setup);
+        let names: Vec<&str> = game.players.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob", "Carol", "Dave"]);
+        assert_eq!(game.players.iter().map(|p| p.id()).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(game.players[2].bot.as_deref(), Some("easy"));
+        assert_eq!(game.players[0].bot, None);
+        assert_eq!(game.players[0].account.as_deref(), Some("alice@example.com"));
+
+        // Bob asked for the first default color, so Alice gets the next one.
+        assert_eq!(game.players[1].color, DEFAULT_COLORS[0]);
+        assert_eq!(game.players[0].color, DEFAULT_COLORS[1]);
+        assert_eq!(game.players[3].color, DEFAULT_COLORS[3]);
+
+        assert_eq!(game.grid.tiles.len(), 19);
+        assert!(game.grid.corners.is_empty());
+        let devcards = rules.devcard_knight_count + rules.devcard_vp_count + rules.devcard_yop_count +
+            rules.devcard_monopoly_count + rules.devcard_road_count;
+        assert_eq!(game.development_cards.len(), devcards as usize);
+        assert_eq!(game.players[0].roads, rules.road_count);
+    }
+
+    #[test]
+    pub fn seed_test() {
+        let a = four_players().shuffle_seats(true).seed(11).build().unwrap();
+        let b = four_players().shuffle_seats(true).seed(11).build().unwrap();
+        assert_eq!(a, b);
+
+        // Every seed gives some seating, so over a few seeds at least one should be shuffled.
+        let shuffled = (0..10).any(|seed| {
+            let game = four_players().shuffle_seats(true).seed(seed).build().unwrap();
+            game.players.iter().map(|p| p.id()).collect::<Vec<_>>() != vec![0, 1, 2, 3]
+        });
+        assert!(shuffled);
+    }
+
+    #[test]
+    pub fn variant_and_grid_test() {
+        let seafarers = Registry::new().get("seafarers").unwrap();
+        let game = four_players().variant(&seafarers).seed(5).build().unwrap();
+        assert_eq!(game.rules, seafarers.rules);
+        assert_eq!(game.grid.tiles.len(), seafarers.map.coords.len());
+
+        let grid = generation::generate_seeded(&seafarers.map, 1).unwrap();
+        let game = four_players().grid(grid.clone()).build().unwrap();
+        assert_eq!(game.grid, grid);
+        assert_eq!(game.rules, Rules::defaults_vanilla());
+    }
+
+    #[test]
+    pub fn production_test() {
+        // Generated tiles start face down, but that only matters when unexplored tiles are hidden.
+        let mut game = four_players().seed(6).build().unwrap();
+        let (coord, tile) = game.grid.tiles.iter()
+            .find(|(_, t)| matches!(t.tile_type, TileType::Resource(_)) && !t.thief)
+            .map(|(c, t)| (*c, *t))
+            .unwrap();
+        let resource = match tile.tile_type {
+            TileType::Resource(resource) => resource,
+            _ => unreachable!(),
+        };
+        let number = tile.number.unwrap();
+        let corner = coord.get_corner_neighbors()[0];
+        game.grid.corners.entry(corner).or_default().settlement = Settlement::Settlement(0);
+
+        // Another tile with the same number could touch the same corner.
+        let mut expected = Resources::new();
+        for neighbor in corner.get_tile_neighbors() {
+            if let Some(TileType::Resource(r)) = game.grid.tiles.get(&neighbor)
+                .filter(|t| t.number == Some(number) && !t.thief)
+                .map(|t| t.tile_type)
+            {
+                expected[r] += 1;
+            }
+        }
+        assert!(expected[resource] >= 1);
+        assert_eq!(game.collect(number as u32)[&0], expected);
+        assert_eq!(game.players[0].resources, expected);
+
+        game.rules.hide_unexplored_tiles = true;
+        for tile in game.grid.tiles.values_mut() {
+            tile.faceup = false;
+        }
+        assert!(game.production(number as u32).is_empty());
+        for tile in game.grid.tiles.values_mut() {
+            tile.faceup = true;
+        }
+        assert_eq!(game.production(number as u32)[&0], expected);
+    }
+
+    #[test]
+    pub fn invalid_players_test() {
+        assert!(GameBuilder::new().build().is_err());
+        assert!(four_players().player(PlayerSetup::human("Alice")).build().is_err());
+        assert!(four_players().player(PlayerSetup::human("Eve").color(DEFAULT_COLORS[0])).build().is_err());
+
+        let mut crowd = GameBuilder::new();
+        for i in 0..=DEFAULT_COLORS.len() {
+            crowd = crowd.player(PlayerSetup::human(&i.to_string()));
+        }
+        assert!(crowd.build().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+#[cfg(test)]
+pub mod cities_and_knights_test {
+    use catan_lib::{Game, GameGrid};
+    use catan_lib::builder::{GameBuilder, PlayerSetup};
+    use catan_lib::cities_and_knights::{self, AttackOutcome, EventDieFace, Improvements, ProgressCard, Track};
+    use catan_lib::configuration::Rules;
+    use catan_lib::events::Event;
+    use catan_lib::player::Resources;
+    use catan_lib::types::{Commodity, Edge, Knight, KnightLevel, Resource, Road, Settlement, Tile, TileType};
+    use hexgrid::hex_coordinates::{self, HexCoord};
+
+    /// A stone tile rolling 8, next to a wheat tile rolling 6.
+    fn grid() -> GameGrid {
+        let mut grid = GameGrid::new();
+        for (x, resource, number) in [(0, Resource::Stone, 8), (1, Resource::Wheat, 6)] {
+            grid.tiles.insert(hex_coordinates::Tile::new(x, 0), Tile {
+                tile_type: TileType::Resource(resource),
+                number: Some(number),
+                thief: false,
+                faceup: true,
+            });
+        }
+        grid
+    }
+
+    fn game() -> Game {
+        GameBuilder::new()
+            .rules(Rules::defaults_cities_and_knights())
+            .grid(grid())
+            .player(PlayerSetup::human("Alice"))
+            .player(PlayerSetup::human("Bob"))
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    /// The corners around the stone tile, in order, so that each is next to the one after it.
+    fn corners() -> Vec<hex_coordinates::Corner> {
+        let mut left = hex_coordinates::Tile::new(0, 0).get_corner_neighbors();
+        let mut ring = vec![left.remove(0)];
+        while !left.is_empty() {
+            let last = ring[ring.len() - 1];
+            let next = left.iter()
+                .position(|c| last.get_edge_neighbors().iter().any(|e| e.get_corner_neighbors().contains(c)))
+                .unwrap();
+            ring.push(left.remove(next));
+        }
+        ring
+    }
+
+    fn edge(a: &hex_coordinates::Corner, b: &hex_coordinates::Corner) -> hex_coordinates::Edge {
+        a.get_edge_neighbors().into_iter()
+            .find(|e| e.get_corner_neighbors().contains(b))
+            .unwrap()
+    }
+
+    fn build(game: &mut Game, corner: &hex_coordinates::Corner, settlement: Settlement) {
+        game.grid.corners.entry(*corner).or_default().settlement = settlement;
+    }
+
+    /// Build roads for `player` from each corner to the next.
+    fn roads(game: &mut Game, player: u64, path: &[hex_coordinates::Corner]) {
+        for pair in path.windows(2) {
+            game.grid.edges.insert(edge(&pair[0], &pair[1]), Edge { port: None, road: Road::Road(player), shoal: None });
+        }
+    }
+
+    fn knight(game: &Game, corner: &hex_coordinates::Corner) -> Option<Knight> {
+        game.grid.corners.get(corner).and_then(|c| c.knight)
+    }
+
+    #[test]
+    pub fn decks_test() {
+        for track in Track::ALL.iter() {
+            assert_eq!(cities_and_knights::deck(*track).len(), 18);
+        }
+        let state = game().cities_and_knights.unwrap();
+        assert_eq!(state.decks[&Track::Politics].len(), 18);
+        assert!(state.decks[&Track::Politics].contains(&ProgressCard::Constitution));
+        assert_eq!(Track::Science.commodity(), Commodity::Paper);
+
+        let vanilla = GameBuilder::new().player(PlayerSetup::human("Alice")).build().unwrap();
+        assert_eq!(vanilla.cities_and_knights, None);
+        assert!(cities_and_knights::thief_can_move(&vanilla));
+    }
+
+    #[test]
+    pub fn production_test() {
+        let mut game = game();
+        let corners = corners();
+        build(&mut game, &corners[0], Settlement::City(0));
+        build(&mut game, &corners[3], Settlement::Settlement(1));
+
+        let production = game.collect(8);
+        let mut city = Resources::of(Resource::Stone, 1);
+        city[Commodity::Coin] = 1;
+        assert_eq!(production[&0], city);
+        assert_eq!(production[&1], Resources::of(Resource::Stone, 1));
+        assert_eq!(game.players[0].resources, city);
+
+        // Without the expansion, a city collects two of the resource instead.
+        game.rules = Rules::defaults_vanilla();
+        assert_eq!(game.production(8)[&0], Resources::of(Resource::Stone, 2));
+    }
+
+    #[test]
+    pub fn improve_test() {
+        let mut game = game();
+        let corners = corners();
+        game.players[0].resources[Commodity::Coin] = 20;
+        assert!(cities_and_knights::improve(&mut game, 0, Track::Politics, None).is_err());
+
+        build(&mut game, &corners[0], Settlement::City(0));
+        build(&mut game, &corners[2], Settlement::City(0));
+        for _ in 0..3 {
+            assert_eq!(cities_and_knights::improve(&mut game, 0, Track::Politics, None), Ok(false));
+        }
+        assert_eq!(game.players[0].resources[Commodity::Coin], 14);
+
+        // Level 4 gets the metropolis, which has to go on one of the player's cities.
+        assert!(cities_and_knights::improve(&mut game, 0, Track::Politics, None).is_err());
+        assert!(cities_and_knights::improve(&mut game, 0, Track::Politics, Some(corners[1])).is_err());
+        assert_eq!(cities_and_knights::improve(&mut game, 0, Track::Politics, Some(corners[2])), Ok(true));
+        let state = game.cities_and_knights.as_ref().unwrap();
+        assert_eq!(state.improvements(0), Improvements { trade: 0, politics: 4, science: 0 });
+        assert_eq!(state.metropolises[&Track::Politics], corners[2]);
+        assert_eq!(game.victory_points(0), 6);
+
+        // Another player at level 5 takes it.
+        build(&mut game, &corners[4], Settlement::City(1));
+        game.players[1].resources[Commodity::Coin] = 15;
+        for _ in 0..4 {
+            assert_eq!(cities_and_knights::improve(&mut game, 1, Track::Politics, None), Ok(false));
+        }
+        assert_eq!(cities_and_knights::improve(&mut game, 1, Track::Politics, Some(corners[4])), Ok(true));
+        assert_eq!(game.cities_and_knights.as_ref().unwrap().metropolises[&Track::Politics], corners[4]);
+        assert_eq!(game.victory_points(0), 4);
+        assert!(cities_and_knights::improve(&mut game, 1, Track::Politics, None).is_err());
+    }
+
+    #[test]
+    pub fn knights_test() {
+        let mut game = game();
+        let corners = corners();
+        game.players[0].resources = Resources::of(Resource::Sheep, 10) + &Resources::of(Resource::Stone, 10);
+        game.players[0].resources[Resource::Wheat] = 10;
+
+        // Knights need to be next to one of the player's roads.
+        assert!(cities_and_knights::build_knight(&mut game, 0, &corners[0]).is_err());
+        roads(&mut game, 0, &corners[0..4]);
+        cities_and_knights::build_knight(&mut game, 0, &corners[0]).unwrap();
+        assert!(cities_and_knights::build_knight(&mut game, 0, &corners[0]).is_err());
+        assert_eq!(knight(&game, &corners[0]), Some(Knight { owner: 0, level: KnightLevel::Basic, active: false }));
+        assert_eq!(game.players[0].resources[Resource::Sheep], 9);
+
+        assert!(cities_and_knights::move_knight(&mut game, 0, &corners[0], &corners[3]).is_err());
+        cities_and_knights::activate_knight(&mut game, 0, &corners[0]).unwrap();
+        assert!(cities_and_knights::activate_knight(&mut game, 0, &corners[0]).is_err());
+        cities_and_knights::promote_knight(&mut game, 0, &corners[0]).unwrap();
+        assert_eq!(knight(&game, &corners[0]).unwrap().level, KnightLevel::Strong);
+        // Mighty knights need politics at level 3.
+        assert!(cities_and_knights::promote_knight(&mut game, 0, &corners[0]).is_err());
+
+        // Moving follows the player's own roads, and deactivates the knight.
+        assert!(cities_and_knights::move_knight(&mut game, 0, &corners[0], &corners[4]).is_err());
+        cities_and_knights::move_knight(&mut game, 0, &corners[0], &corners[3]).unwrap();
+        assert_eq!(knight(&game, &corners[0]), None);
+        assert_eq!(knight(&game, &corners[3]), Some(Knight { owner: 0, level: KnightLevel::Strong, active: false }));
+    }
+
+    #[test]
+    pub fn displace_knight_test() {
+        let mut game = game();
+        let corners = corners();
+        roads(&mut game, 0, &corners[0..3]);
+        roads(&mut game, 1, &corners[2..5]);
+        let strong = Knight { owner: 0, level: KnightLevel::Strong, active: true };
+        let basic = Knight { owner: 1, level: KnightLevel::Basic, active: true };
+        game.grid.corners.entry(corners[0]).or_default().knight = Some(strong);
+        game.grid.corners.entry(corners[2]).or_default().knight = Some(basic);
+
+        // Bob's knight can retreat along his roads, so it has to.
+        assert!(cities_and_knights::displace_knight(&mut game, 0, &corners[0], &corners[2], None).is_err());
+        assert!(cities_and_knights::displace_knight(&mut game, 0, &corners[0], &corners[2], Some(&corners[1])).is_err());
+        cities_and_knights::displace_knight(&mut game, 0, &corners[0], &corners[2], Some(&corners[4])).unwrap();
+        assert_eq!(knight(&game, &corners[0]), None);
+        assert_eq!(knight(&game, &corners[2]), Some(Knight { active: false, ..strong }));
+        assert_eq!(knight(&game, &corners[4]), Some(basic));
+
+        // A knight can't displace one that is as strong as it.
+        game.grid.corners.entry(corners[2]).or_default().knight = Some(strong);
+        game.grid.corners.entry(corners[4]).or_default().knight = Some(Knight { owner: 1, ..strong });
+        assert!(cities_and_knights::displace_knight(&mut game, 0, &corners[2], &corners[4], None).is_err());
+    }
+
+    #[test]
+    pub fn barbarians_test() {
+        let mut game = game();
+        let corners = corners();
+        build(&mut game, &corners[0], Settlement::City(0));
+        build(&mut game, &corners[2], Settlement::City(1));
+        assert!(!cities_and_knights::thief_can_move(&game));
+
+        // Two cities against no knights.
+        let attack = cities_and_knights::barbarians_attack(&mut game).unwrap();
+        assert_eq!((attack.strength, attack.defense), (2, 0));
+        assert_eq!(attack.outcome, AttackOutcome::Pillaged { players: vec![0, 1] });
+        assert!(cities_and_knights::pillage_city(&mut game, 0, &corners[2]).is_err());
+        cities_and_knights::pillage_city(&mut game, 0, &corners[0]).unwrap();
+        assert_eq!(game.grid.corners[&corners[0]].settlement, Settlement::Settlement(0));
+        assert!(cities_and_knights::thief_can_move(&game));
+
+        // One city against a strong knight.
+        game.grid.corners.entry(corners[4]).or_default().knight =
+            Some(Knight { owner: 0, level: KnightLevel::Strong, active: true });
+        let attack = cities_and_knights::barbarians_attack(&mut game).unwrap();
+        assert_eq!(attack.outcome, AttackOutcome::Defended { defender: Some(0), tied: vec![] });
+        assert!(!knight(&game, &corners[4]).unwrap().active);
+        assert_eq!(game.victory_points(0), 2);
+        assert_eq!(game.events.last(), Some(&Event::BarbarianAttack(attack)));
+    }
+
+    #[test]
+    pub fn progress_cards_test() {
+        let mut game = game();
+        let mut held = 0;
+        while held < 4 {
+            let card = cities_and_knights::draw_progress_card(&mut game, 0, Track::Trade).unwrap().unwrap();
+            assert!(!card.is_victory_point());
+            held += 1;
+        }
+        // The hand is full.
+        assert_eq!(cities_and_knights::draw_progress_card(&mut game, 0, Track::Trade), Ok(None));
+        let state = game.cities_and_knights.as_ref().unwrap();
+        assert_eq!(state.progress_cards[&0].len(), 4);
+        assert_eq!(state.decks[&Track::Trade].len(), 14);
+    }
+
+    #[test]
+    pub fn roll_test() {
+        let mut game = game();
+        build(&mut game, &corners()[0], Settlement::City(0));
+        let mut ships = 0;
+        let mut attacked = false;
+        for _ in 0..100 {
+            let roll = cities_and_knights::roll(&mut game, 0).unwrap();
+            let position = game.cities_and_knights.as_ref().unwrap().barbarian_position;
+            match roll.event {
+                EventDieFace::Ship => {
+                    ships += 1;
+                    assert_eq!(roll.attack.is_some(), position == 0);
+                    attacked |= roll.attack.is_some();
+                },
+                EventDieFace::Gate(_) => assert!(roll.attack.is_none()),
+            }
+            assert_eq!(roll.production.contains_key(&0), roll.dice[0] + roll.dice[1] == 8);
+        }
+        assert!(ships > 30 && ships < 70);
+        assert!(attacked);
+        let event_dice = game.events.iter().filter(|e| matches!(e, Event::EventDie { .. })).count();
+        assert_eq!(event_dice, 100);
+
+        assert!(cities_and_knights::roll(&mut GameBuilder::new().player(PlayerSetup::human("Alice")).build().unwrap(), 0).is_err());
+    }
+}
@@ -1,10 +1,215 @@
 #[cfg(test)]
 pub mod configuration_test {
     use catan_lib;
+    use catan_lib::configuration::{Rules, Costs, MapGenerationSettings, PinnedTile, NumberPlacement};
+    use catan_lib::configuration::registry::Registry;
+    use catan_lib::generation::{self, GenerationError};
+    use catan_lib::types::{TileType, Resource, Purchase};
+    use catan_lib::player::Resources;
+    use catan_lib::configuration::shape::BoardShape;
+    use hexgrid::hex_coordinates::Tile;
 
     #[test]
     pub fn configuration_vanilla_test() {
         // Make sure it doesn't panic.
         let _rules = catan_lib::configuration::Rules::defaults_vanilla();
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn hexagon_shape_test() {
+        let single = BoardShape::Hexagon { center: Tile::new(3, -1), radius: 0 };
+        assert_eq!(single.resolve().unwrap(), vec![Tile::new(3, -1)]);
+
+        let board = BoardShape::Hexagon { center: Tile::new(0, 0), radius: 2 }.resolve().unwrap();
+        assert_eq!(board.len(), 19);
+        assert!(board.contains(&Tile::new(2, -2)));
+        assert!(!board.contains(&Tile::new(2, 2)));
+        assert_eq!(board.first(), Some(&Tile::new(0, -2)));
+    }
+
+    #[test]
+    pub fn rectangle_shape_test() {
+        let board = BoardShape::Rectangle { corner: Tile::new(0, 0), width: 3, height: 4 }.resolve().unwrap();
+        assert_eq!(board.len(), 12);
+        assert!(board.contains(&Tile::new(-1, 3)));
+        assert!(board.contains(&Tile::new(1, 3)));
+        assert!(!board.contains(&Tile::new(2, 3)));
+    }
+
+    #[test]
+    pub fn mask_shape_test() {
+        let mask = BoardShape::Mask {
+            origin: Tile::new(1, 0),
+            rows: vec![
+                "  x x x".to_string(),
+                " x x x x".to_string(),
+                "x x . x x".to_string(),
+                " x x x x".to_string(),
+                "  x x x".to_string(),
+            ]
+        };
+        let mut hexagon = BoardShape::Hexagon { center: Tile::new(2, 2), radius: 2 }.resolve().unwrap();
+        hexagon.retain(|c| *c != Tile::new(2, 2));
+        assert_eq!(mask.resolve().unwrap(), hexagon);
+
+        let misaligned = BoardShape::Mask { origin: Tile::new(0, 0), rows: vec!["x x".to_string(), "x".to_string()] };
+        assert!(misaligned.resolve().is_err());
+    }
+
+    #[test]
+    pub fn combined_shape_test() {
+        let big = BoardShape::Hexagon { center: Tile::new(0, 0), radius: 2 };
+        let small = BoardShape::Hexagon { center: Tile::new(0, 0), radius: 1 };
+        let ring = BoardShape::Difference { shape: Box::new(big.clone()), minus: vec![small.clone()] };
+        assert_eq!(ring.resolve().unwrap().len(), 12);
+
+        let union = BoardShape::Union { shapes: vec![ring, small] };
+        assert_eq!(union.resolve().unwrap(), big.resolve().unwrap());
+    }
+
+    #[test]
+    pub fn settings_shape_test() {
+        let vanilla = MapGenerationSettings::defaults_vanilla();
+        assert_eq!(vanilla.coords.len(), 19);
+        assert!(vanilla.shape.is_none());
+        assert_eq!(MapGenerationSettings::defaults_seafarers().coords.len(), 44);
+
+        let mut json = serde_json::to_value(&vanilla).unwrap();
+        json["coords"] = serde_json::json!(["10,10,Tile"]);
+        json["shape"] = serde_json::json!({"type": "Hexagon", "center": "0,0,Tile", "radius": 1});
+        let settings = MapGenerationSettings::from_json(&json.to_string()).unwrap();
+        assert_eq!(settings.coords.len(), 8);
+        assert!(settings.coords.contains(&Tile::new(10, 10)));
+    }
+
+    #[test]
+    pub fn validate_test() {
+        for config in [
+            MapGenerationSettings::defaults_vanilla(),
+            MapGenerationSettings::defaults_vanilla56(),
+            MapGenerationSettings::defaults_seafarers(),
+            MapGenerationSettings::defaults_sheepland(),
+        ] {
+            assert_eq!(config.validate(), Ok(()));
+        }
+
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        config.coords.push(config.coords[0]);
+        config.min_corner_score = 12;
+        config.numbers[0] = 7;
+        config.numbers.push(13);
+        config.pinned_tiles.push(PinnedTile { coord: Tile::new(9, 9), tile_type: TileType::Desert, number: Some(6) });
+        config.pinned_tiles.push(PinnedTile { coord: Tile::new(2, 2), tile_type: TileType::Desert, number: None });
+        let problems = config.validate().unwrap_err();
+        assert_eq!(problems.len(), 9, "{:?}", problems);
+
+        assert_eq!(generation::generate_tiles(&config), Err(GenerationError::InvalidSettings(problems)));
+    }
+
+    #[test]
+    pub fn validate_pinned_numbers_test() {
+        let wheat = TileType::Resource(Resource::Wheat);
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        config.pinned_tiles.push(PinnedTile { coord: config.coords[0], tile_type: wheat, number: Some(2) });
+        assert_eq!(config.validate(), Ok(()));
+
+        config.pinned_tiles.push(PinnedTile { coord: config.coords[1], tile_type: wheat, number: Some(2) });
+        assert_eq!(config.validate().unwrap_err().len(), 1);
+
+        config.pinned_tiles[1].number = Some(6);
+        config.pinned_tiles.push(PinnedTile { coord: config.coords[2], tile_type: TileType::Desert, number: Some(5) });
+        assert_eq!(
+            config.validate(),
+            Err(vec![format!("Pinned tile {} has a number, but isn't a resource tile", config.coords[2])])
+        );
+
+        // With a fixed layout, pinned numbers don't come out of `numbers`.
+        config.pinned_tiles.pop();
+        config.pinned_tiles[1].number = Some(2);
+        config.number_placement = NumberPlacement::Fixed {
+            numbers: config.coords.iter().map(|c| (*c, 8)).collect(),
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    pub fn costs_test() {
+        let rules = Rules::defaults_vanilla();
+        assert_eq!(rules.costs, Costs::default());
+        assert_eq!(rules.cost(Purchase::City).total(), 5);
+
+        let mut registry = Registry::new();
+        registry.add_json(r#"{
+            "name": "cheap-cities",
+            "extends": "vanilla",
+            "rules": {"costs": {"city": {"Stone": 2}}}
+        }"#).unwrap();
+        let cheap = registry.get("cheap-cities").unwrap().rules;
+        assert_eq!(*cheap.cost(Purchase::City), Resources::of(Resource::Wheat, 2) + &Resources::of(Resource::Stone, 2));
+        assert_eq!(cheap.cost(Purchase::Road), rules.cost(Purchase::Road));
+
+        let hand = Resources::of(Resource::Wheat, 2) + &Resources::of(Resource::Stone, 2);
+        assert!(cheap.can_afford(&hand, Purchase::City));
+        assert!(!rules.can_afford(&hand, Purchase::City));
+        assert!(!rules.can_afford(&hand, Purchase::Road));
+    }
+
+    #[test]
+    pub fn registry_test() {
+        let mut registry = Registry::new();
+        let names: Vec<String> = registry.list().into_iter().map(|v| v.name).collect();
+        assert_eq!(names, vec!["vanilla", "vanilla56", "seafarers", "cities_and_knights", "fishermen", "sheepland"]);
+        assert_eq!(registry.get("vanilla").unwrap().map, MapGenerationSettings::defaults_vanilla());
+
+        registry.add_json(r#"{
+            "name": "seafarers-12",
+            "description": "Seafarers to 12 points",
+            "extends": "seafarers",
+            "rules": {"victory_points": 12}
+        }"#).unwrap();
+        registry.add_json(r#"{
+            "name": "dry-seafarers-12",
+            "extends": "seafarers-12",
+            "map": {"ocean_count": 0, "desert_count": 17}
+        }"#).unwrap();
+
+        let twelve = registry.get("seafarers-12").unwrap();
+        assert_eq!(twelve.rules.victory_points, 12);
+        assert_eq!(twelve.rules.ship_count, Rules::defaults_seafarers().ship_count);
+        assert_eq!(twelve.map, MapGenerationSettings::defaults_seafarers());
+
+        let dry = registry.get("dry-seafarers-12").unwrap();
+        assert_eq!(dry.rules.victory_points, 12);
+        assert_eq!(dry.map.ocean_count, 0);
+        assert_eq!(dry.map.coords.len(), 44);
+        let info = registry.list().pop().unwrap();
+        assert_eq!(info.extends, Some("seafarers-12".to_string()));
+        assert!(!info.builtin);
+
+        assert!(registry.add_json(r#"{"name": "vanilla", "extends": "seafarers"}"#).is_err());
+        registry.add_json(r#"{"name": "a", "extends": "b"}"#).unwrap();
+        registry.add_json(r#"{"name": "b", "extends": "a"}"#).unwrap();
+        assert!(registry.get("a").is_err());
+        registry.add_json(r#"{"name": "orphan", "extends": "nothing"}"#).unwrap();
+        assert!(registry.get("orphan").is_err());
+        assert!(registry.get("nothing").is_err());
+    }
+
+    #[test]
+    pub fn registry_files_test() {
+        let dir = std::env::temp_dir().join(format!("catan_registry_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("big.json"), r#"{"name": "big", "extends": "vanilla", "rules": {"city_count": 8}}"#).unwrap();
+        std::fs::write(dir.join("small.toml"), "name = \"small\"\nextends = \"big\"\n\n[rules]\nsettlement_count = 3\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a variant").unwrap();
+
+        let mut registry = Registry::new();
+        let loaded = registry.load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        loaded.unwrap();
+        assert_eq!(registry.get("big").unwrap().rules.city_count, 8);
+        let small = registry.get("small").unwrap();
+        assert_eq!((small.rules.city_count, small.rules.settlement_count), (8, 3));
+        assert_eq!(registry.list().len(), 8);
+    }
+}
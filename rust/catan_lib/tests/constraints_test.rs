@@ -0,0 +1,208 @@
+#[cfg(test)]
+pub mod constraints_test {
+    use std::collections::HashSet;
+    use catan_lib::{GameGrid, configuration::MapGenerationSettings, generation};
+    use catan_lib::configuration::shape::BoardShape;
+    use catan_lib::generation::constraints::{Constraint, GenerationConstraint};
+    use catan_lib::types::{TileType, Resource};
+    use hexgrid::Grid;
+    use hexgrid::hex_coordinates;
+
+    fn generate(config: &MapGenerationSettings) -> GameGrid {
+        let mut grid = generation::generate_tiles(config).unwrap();
+        generation::generate_numbers(config, &mut grid).unwrap();
+        grid
+    }
+
+    fn with_constraint(mut config: MapGenerationSettings, constraint: GenerationConstraint) -> MapGenerationSettings {
+        config.constraints.push(constraint);
+        config
+    }
+
+    fn pips(n: i32) -> i32 {
+        6 - (7 - n).abs()
+    }
+
+    #[test]
+    pub fn no_adjacent_numbers_test() {
+        let numbers = vec![6, 8];
+        let config = with_constraint(
+            MapGenerationSettings::defaults_vanilla(),
+            GenerationConstraint::NoAdjacentNumbers { numbers: numbers.clone() }
+        );
+        let grid = generate(&config);
+
+        for (coord, tile) in grid.tiles.iter().filter(|(_, t)| t.number.map_or(false, |n| numbers.contains(&n))) {
+            for (_, neighbor) in grid.get_tile_neighbors(coord) {
+                assert!(!neighbor.number.map_or(false, |n| numbers.contains(&n)), "{:?} is next to {:?}", tile, neighbor);
+            }
+        }
+    }
+
+    #[test]
+    pub fn no_same_number_adjacent_test() {
+        let config = with_constraint(MapGenerationSettings::defaults_vanilla(), GenerationConstraint::NoSameNumberAdjacent);
+        let grid = generate(&config);
+
+        for (coord, tile) in grid.tiles.iter().filter(|(_, t)| t.number.is_some()) {
+            for (_, neighbor) in grid.get_tile_neighbors(coord) {
+                assert_ne!(tile.number, neighbor.number);
+            }
+        }
+    }
+
+    #[test]
+    pub fn max_resource_pips_test() {
+        let config = with_constraint(MapGenerationSettings::defaults_vanilla(), GenerationConstraint::MaxResourcePips { max: 13 });
+        let grid = generate(&config);
+
+        for resource in &[Resource::Wheat, Resource::Sheep, Resource::Clay, Resource::Stone, Resource::Wood] {
+            let total: i32 = grid.tiles.values()
+                .filter(|t| t.tile_type == TileType::Resource(*resource))
+                .filter_map(|t| t.number.map(pips))
+                .sum();
+            assert!(total <= 13, "{:?} has {} pips", resource, total);
+        }
+    }
+
+    #[test]
+    pub fn desert_in_center_test() {
+        let config = with_constraint(MapGenerationSettings::defaults_vanilla(), GenerationConstraint::DesertInCenter);
+        let grid = generate(&config);
+
+        assert_eq!(grid.tiles[&hex_coordinates::Tile::new(2, 2)].tile_type, TileType::Desert);
+    }
+
+    #[test]
+    pub fn contiguous_ocean_test() {
+        let config = MapGenerationSettings::defaults_seafarers();
+        let grid = generation::generate_tiles(&config).unwrap();
+
+        let oceans: Vec<hex_coordinates::Tile> = grid.tiles.iter()
+            .filter(|(_, tile)| tile.tile_type == TileType::Ocean)
+            .map(|(coord, _)| *coord)
+            .collect();
+        let mut seen: HashSet<hex_coordinates::Tile> = oceans[..1].iter().cloned().collect();
+        let mut queue = oceans[..1].to_vec();
+        while let Some(coord) = queue.pop() {
+            for (neighbor, data) in grid.get_tile_neighbors(&coord) {
+                if data.tile_type == TileType::Ocean && seen.insert(neighbor) {
+                    queue.push(neighbor);
+                }
+            }
+        }
+        assert_eq!(seen.len(), oceans.len());
+    }
+
+    #[test]
+    pub fn min_island_size_test() {
+        let config = with_constraint(MapGenerationSettings::defaults_seafarers(), GenerationConstraint::MinIslandSize { min: 4 });
+        let grid = generation::generate_tiles(&config).unwrap();
+
+        for island in islands(&grid) {
+            assert!(island.len() >= 4, "Island at {} only has {} tiles", island[0], island.len());
+        }
+    }
+
+    /// Every group of connected tiles that aren't ocean.
+    fn islands(grid: &GameGrid) -> Vec<Vec<hex_coordinates::Tile>> {
+        let mut islands = Vec::new();
+        let mut seen = HashSet::new();
+        for (coord, tile) in grid.tiles.iter() {
+            if tile.tile_type == TileType::Ocean || seen.contains(coord) {
+                continue;
+            }
+            let mut island = vec![*coord];
+            seen.insert(*coord);
+            let mut i = 0;
+            while i < island.len() {
+                for (neighbor, data) in grid.get_tile_neighbors(&island[i]) {
+                    if data.tile_type != TileType::Ocean && seen.insert(neighbor) {
+                        island.push(neighbor);
+                    }
+                }
+                i += 1;
+            }
+            islands.push(island);
+        }
+        islands
+    }
+
+    #[test]
+    pub fn archipelago_test() {
+        // A bigger board than usual, so there is enough ocean to keep the islands apart.
+        let mut config = MapGenerationSettings::defaults_seafarers();
+        config.coords = BoardShape::Hexagon { center: hex_coordinates::Tile::new(0, 0), radius: 4 }.resolve().unwrap();
+        config.ocean_count = 33;
+        config.constraints.extend(vec![
+            GenerationConstraint::IslandCount { min: 2, max: 4 },
+            GenerationConstraint::MainIsland { min: 12 },
+            GenerationConstraint::MaxIslandSize { max: 20 },
+            GenerationConstraint::WaterGap { min: 2 },
+            GenerationConstraint::IslandResources { min: 3 },
+        ]);
+
+        let grid = generation::generate_tiles(&config).unwrap();
+        let islands = islands(&grid);
+        assert!(islands.len() >= 2 && islands.len() <= 4, "{} islands", islands.len());
+        assert!(islands.iter().any(|i| i.len() >= 12));
+        for island in islands.iter() {
+            assert!(island.len() <= 20);
+            let kinds: HashSet<TileType> = island.iter()
+                .map(|c| grid.tiles[c].tile_type)
+                .filter(|t| matches!(t, TileType::Resource(_)))
+                .collect();
+            assert!(kinds.len() >= 3, "Island at {} only has {} resources", island[0], kinds.len());
+            for other in islands.iter().filter(|o| o[0] != island[0]) {
+                for (a, b) in island.iter().flat_map(|a| other.iter().map(move |b| (a, b))) {
+                    let (dx, dy) = (a.x - b.x, a.y - b.y);
+                    assert!((dx.abs() + dy.abs() + (dx + dy).abs()) / 2 > 2, "{} is too close to {}", a, b);
+                }
+            }
+        }
+    }
+
+    struct NoWheatAt(hex_coordinates::Tile);
+
+    impl Constraint<GameGrid> for NoWheatAt {
+        fn allows_tile(&self, _config: &MapGenerationSettings, _grid: &GameGrid, coord: &hex_coordinates::Tile, tile_type: TileType) -> bool {
+            *coord != self.0 || tile_type != TileType::Resource(Resource::Wheat)
+        }
+    }
+
+    #[test]
+    pub fn custom_constraint_test() {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let coord = hex_coordinates::Tile::new(2, 2);
+
+        for _ in 0..10 {
+            let mut grid = GameGrid::new();
+            generation::generate_tiles_with(&config, &mut grid, &[&NoWheatAt(coord)]).unwrap();
+            assert_ne!(grid.tiles[&coord].tile_type, TileType::Resource(Resource::Wheat));
+        }
+    }
+
+    #[test]
+    pub fn constraint_json_test() {
+        let json = r#"[
+            {"type": "NoAdjacentNumbers", "numbers": [6, 8]},
+            {"type": "DesertInCenter"},
+            {"type": "MinIslandSize", "min": 3}
+        ]"#;
+        let constraints: Vec<GenerationConstraint> = serde_json::from_str(json).unwrap();
+        assert_eq!(constraints, vec![
+            GenerationConstraint::NoAdjacentNumbers { numbers: vec![6, 8] },
+            GenerationConstraint::DesertInCenter,
+            GenerationConstraint::MinIslandSize { min: 3 },
+        ]);
+    }
+
+    #[test]
+    pub fn settings_constraints_test() {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let all = config.all_constraints();
+        assert!(all.contains(&GenerationConstraint::AvoidAdjacent));
+        assert!(all.contains(&GenerationConstraint::ContiguousOcean));
+        assert!(all.contains(&GenerationConstraint::CornerScore { min: config.min_corner_score, max: config.max_corner_score }));
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+pub mod dice_test {
+    use rand::SeedableRng;
+    use catan_lib::{Game, save};
+    use catan_lib::builder::{GameBuilder, PlayerSetup};
+    use catan_lib::configuration::Rules;
+    use catan_lib::dice::{Dice, DiceMode};
+    use catan_lib::events::Event;
+    use catan_lib::rng::GameRng;
+
+    /// How many times each total comes up in `rolls` rolls.
+    fn totals(mode: DiceMode, rolls: usize) -> [usize; 13] {
+        let mut rng = GameRng::seed_from_u64(1);
+        let mut dice = Dice::new();
+        let mut totals = [0; 13];
+        for _ in 0..rolls {
+            let [a, b] = dice.roll(&mode, &mut rng);
+            assert!((1..=6).contains(&a) && (1..=6).contains(&b));
+            totals[(a + b) as usize] += 1;
+        }
+        totals
+    }
+
+    #[test]
+    pub fn standard_test() {
+        let totals = totals(DiceMode::Standard, 36_000);
+        for (total, count) in totals.iter().enumerate().skip(2) {
+            let expected = 6 - (7 - total as i32).abs();
+            let difference = *count as f64 / 1000.0 - expected as f64;
+            assert!(difference.abs() < 0.5, "{} came up {} times", total, count);
+        }
+    }
+
+    #[test]
+    pub fn deck_test() {
+        // With no reshuffling until the deck runs out, every 36 rolls are exactly one deck.
+        let totals = totals(DiceMode::Deck { reshuffle_at: 0 }, 360);
+        for (total, count) in totals.iter().enumerate().skip(2) {
+            assert_eq!(*count, 10 * (6 - (7 - total as i32).abs()) as usize);
+        }
+
+        let mut rng = GameRng::seed_from_u64(2);
+        let mut dice = Dice::new();
+        let mode = DiceMode::Deck { reshuffle_at: 30 };
+        let mut rolls: Vec<[u32; 2]> = (0..6).map(|_| dice.roll(&mode, &mut rng)).collect();
+        rolls.sort();
+        rolls.dedup();
+        assert_eq!(rolls.len(), 6);
+    }
+
+    #[test]
+    pub fn balanced_test() {
+        // Balanced dice still roll every total about as often as real dice, over a long game.
+        let totals = totals(DiceMode::Balanced { memory: 10 }, 36_000);
+        for (total, count) in totals.iter().enumerate().skip(2) {
+            let expected = 6 - (7 - total as i32).abs();
+            let difference = *count as f64 / 1000.0 - expected as f64;
+            assert!(difference.abs() < 1.0, "{} came up {} times", total, count);
+        }
+
+        // But the same total comes up twice in a row less often.
+        let repeats = |mode: DiceMode| {
+            let mut rng = GameRng::seed_from_u64(3);
+            let mut dice = Dice::new();
+            let rolls: Vec<u32> = (0..10_000).map(|_| dice.roll(&mode, &mut rng).iter().sum()).collect();
+            rolls.windows(2).filter(|w| w[0] == w[1]).count()
+        };
+        assert!(repeats(DiceMode::Balanced { memory: 10 }) < repeats(DiceMode::Standard));
+    }
+
+    #[test]
+    pub fn balanced_shorter_memory_test() {
+        // Rolling with a long memory and then a short one, as if the rules changed in the middle
+        // of a game, forgets the extra rolls instead of overflowing.
+        let mut rng = GameRng::seed_from_u64(4);
+        let mut dice = Dice::new();
+        for _ in 0..100 {
+            dice.roll(&DiceMode::Balanced { memory: 20 }, &mut rng);
+        }
+        for memory in [1, 0] {
+            for _ in 0..100 {
+                let [a, b] = dice.roll(&DiceMode::Balanced { memory }, &mut rng);
+                assert!((1..=6).contains(&a) && (1..=6).contains(&b));
+            }
+        }
+    }
+
+    #[test]
+    pub fn game_roll_test() {
+        let mut rules = Rules::defaults_vanilla();
+        rules.dice = DiceMode::Balanced { memory: 5 };
+        let new_game = || GameBuilder::new()
+            .rules(rules.clone())
+            .player(PlayerSetup::human("Alice"))
+            .player(PlayerSetup::human("Bob"))
+            .seed(9)
+            .build()
+            .unwrap();
+
+        let mut game = new_game();
+        let first: Vec<[u32; 2]> = (0..5).map(|_| game.roll_dice(0)).collect();
+        assert_eq!(game.players[0].stats.dice_rolls.iter().sum::<u32>(), 5);
+        assert_eq!(game.events.len(), 5);
+        assert_eq!(game.events[0], Event::Roll { player: 0, dice: first[0] });
+        // A seed gives the same rolls everywhere, so these can be written down.
+        assert_eq!(first, vec![[1, 4], [2, 5], [2, 4], [2, 6], [4, 1]]);
+
+        // Saving and loading in the middle doesn't change what comes next.
+        let mut loaded: Game = save::from_json(&save::to_json(&game).unwrap()).unwrap();
+        let rolls: Vec<[u32; 2]> = (0..20).map(|_| game.roll_dice(1)).collect();
+        let loaded_rolls: Vec<[u32; 2]> = (0..20).map(|_| loaded.roll_dice(1)).collect();
+        assert_eq!(rolls, loaded_rolls);
+
+        let mut replay = new_game();
+        assert_eq!((0..5).map(|_| replay.roll_dice(0)).collect::<Vec<_>>(), first);
+    }
+}
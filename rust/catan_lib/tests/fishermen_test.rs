@@ -0,0 +1,199 @@
+#[cfg(test)]
+pub mod fishermen_test {
+    use std::collections::HashSet;
+    use catan_lib::Game;
+    use catan_lib::builder::{GameBuilder, PlayerSetup};
+    use catan_lib::configuration::registry::Registry;
+    use catan_lib::events::Event;
+    use catan_lib::scenarios::{self, ScenarioState};
+    use catan_lib::scenarios::fishermen::{self, FishExchange, State};
+    use catan_lib::player::Resources;
+    use catan_lib::types::{DevelopmentCard, FishToken, TileType, Resource, Road, Settlement};
+    use hexgrid::hex_coordinates::{self, HexCoord};
+
+    fn game(seed: u64) -> Game {
+        GameBuilder::new()
+            .variant(&Registry::new().get("fishermen").unwrap())
+            .player(PlayerSetup::human("Alice"))
+            .player(PlayerSetup::human("Bob"))
+            .seed(seed)
+            .build()
+            .unwrap()
+    }
+
+    fn state(game: &mut Game) -> &mut State {
+        match game.scenario.as_mut() {
+            Some(ScenarioState::Fishermen(state)) => state,
+            _ => panic!("The game isn't playing Fishermen of Catan"),
+        }
+    }
+
+    /// The shoals, sorted by number.
+    fn shoals(game: &Game) -> Vec<(i32, hex_coordinates::Edge)> {
+        let mut shoals: Vec<_> = game.grid.edges.iter()
+            .filter_map(|(coords, e)| e.shoal.map(|s| (s.number, *coords)))
+            .collect();
+        shoals.sort_by_key(|(number, _)| *number);
+        shoals
+    }
+
+    #[test]
+    pub fn setup_test() {
+        let mut game = game(5);
+        let shoals = shoals(&game);
+        let numbers: Vec<i32> = shoals.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, vec![4, 5, 6, 8, 9, 10]);
+        let mut corners = HashSet::new();
+        for (_, edge) in shoals.iter() {
+            assert!(fishermen::is_coastal(&game.grid, edge));
+            for corner in edge.get_corner_neighbors() {
+                assert!(corners.insert(corner), "Two shoals share {}", corner);
+            }
+        }
+        assert_eq!(shoals, self::shoals(&self::game(5)));
+
+        let state = state(&mut game);
+        assert_eq!(state.bag.len(), 30);
+        assert_eq!(state.bag.iter().filter(|t| **t == FishToken::OldBoot).count(), 1);
+
+        let vanilla = GameBuilder::new().player(PlayerSetup::human("Alice")).build().unwrap();
+        assert_eq!(vanilla.scenario, None);
+        assert!(vanilla.grid.edges.values().all(|e| e.shoal.is_none()));
+    }
+
+    #[test]
+    pub fn collect_test() {
+        let mut game = game(5);
+        let is_resource = |game: &Game, tile: &hex_coordinates::Tile| {
+            matches!(game.grid.tiles.get(tile).map(|t| t.tile_type), Some(TileType::Resource(_)))
+        };
+        let (number, edge) = shoals(&game).into_iter()
+            .find(|(_, edge)| edge.get_tile_neighbors().iter().any(|t| is_resource(&game, t)))
+            .unwrap();
+        let corners = edge.get_corner_neighbors();
+        game.grid.corners.entry(corners[0]).or_default().settlement = Settlement::Settlement(0);
+        game.grid.corners.entry(corners[1]).or_default().settlement = Settlement::City(1);
+
+        // Give the land next to the shoal the same number, so that resources and fish both come
+        // in on the same roll.
+        let land = edge.get_tile_neighbors().into_iter().find(|t| is_resource(&game, t)).unwrap();
+        let tile = game.grid.tiles.get_mut(&land).unwrap();
+        tile.number = Some(number);
+        tile.thief = false;
+
+        let mut expected = [Resources::new(), Resources::new()];
+        for (player, corner) in corners.iter().enumerate() {
+            for neighbor in corner.get_tile_neighbors() {
+                if let Some(TileType::Resource(resource)) = game.grid.tiles.get(&neighbor)
+                    .filter(|t| t.number == Some(number) && !t.thief)
+                    .map(|t| t.tile_type)
+                {
+                    expected[player][resource] += player as u32 + 1;
+                }
+            }
+        }
+        assert!(expected.iter().all(|r| !r.is_empty()));
+
+        game.collect(number as u32);
+        assert_eq!(game.players[0].resources, expected[0]);
+        assert_eq!(game.players[1].resources, expected[1]);
+        let caught: Vec<_> = game.events.iter()
+            .filter_map(|e| match e {
+                Event::FishCaught { player, token } => Some((*player, *token)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(caught.iter().map(|(p, _)| *p).collect::<Vec<_>>(), vec![0, 1, 1]);
+
+        let state = state(&mut game);
+        assert_eq!(state.bag.len(), 27);
+        for player in [0, 1] {
+            let fish: u32 = caught.iter()
+                .filter_map(|(p, t)| match t {
+                    FishToken::Fish(fish) if *p == player => Some(*fish),
+                    _ => None,
+                })
+                .sum();
+            assert_eq!(state.fish(player), fish);
+        }
+    }
+
+    #[test]
+    pub fn exchange_test() {
+        let mut game = game(5);
+        for tile in game.grid.tiles.values_mut() {
+            tile.thief = false;
+        }
+        state(&mut game).fish.insert(0, vec![3, 2, 1]);
+
+        // Nothing is spent when the exchange can't happen.
+        assert!(fishermen::exchange(&mut game, 0, FishExchange::RemoveThief).is_err());
+        assert!(fishermen::exchange(&mut game, 0, FishExchange::StealResource(1)).is_err());
+        assert!(fishermen::exchange(&mut game, 0, FishExchange::DevelopmentCard).is_err());
+        assert_eq!(state(&mut game).fish(0), 6);
+
+        // Paying 4 with 3 + 1 wastes nothing.
+        fishermen::exchange(&mut game, 0, FishExchange::TakeResource(Resource::Wood)).unwrap();
+        assert_eq!(game.players[0].resources[Resource::Wood], 1);
+        assert_eq!(state(&mut game).fish[&0], vec![2]);
+        assert_eq!(state(&mut game).spent, vec![FishToken::Fish(1), FishToken::Fish(3)]);
+
+        state(&mut game).fish.insert(0, vec![2, 1]);
+        game.players[1].resources[Resource::Sheep] = 1;
+        fishermen::exchange(&mut game, 0, FishExchange::StealResource(1)).unwrap();
+        assert_eq!(game.players[0].resources[Resource::Sheep], 1);
+        assert!(game.players[1].resources.is_empty());
+        assert_eq!(state(&mut game).fish(0), 0);
+
+        state(&mut game).fish.insert(0, vec![2, 2, 3]);
+        let deck = game.development_cards.len();
+        fishermen::exchange(&mut game, 0, FishExchange::DevelopmentCard).unwrap();
+        assert_eq!(game.development_cards.len(), deck - 1);
+        assert_eq!(game.players[0].hidden_devcards.len(), 1);
+        assert_eq!(
+            game.events.last(),
+            Some(&Event::FishExchanged { player: 0, exchange: FishExchange::DevelopmentCard })
+        );
+    }
+
+    #[test]
+    pub fn build_road_test() {
+        let mut game = game(5);
+        let tile = *game.grid.tiles.keys().next().unwrap();
+        let corner = tile.get_corner_neighbors()[0];
+        let edges = corner.get_edge_neighbors();
+        let edge = edges.iter().find(|e| e.get_tile_neighbors().contains(&tile)).unwrap();
+        state(&mut game).fish.insert(0, vec![3, 3]);
+
+        // The road has to be next to something of the player's.
+        assert!(fishermen::exchange(&mut game, 0, FishExchange::BuildRoad(*edge)).is_err());
+        game.grid.corners.entry(corner).or_default().settlement = Settlement::Settlement(0);
+        fishermen::exchange(&mut game, 0, FishExchange::BuildRoad(*edge)).unwrap();
+        assert_eq!(game.grid.edges[edge].road, Road::Road(0));
+        assert_eq!(game.players[0].roads, 14);
+        assert_eq!(state(&mut game).fish(0), 0);
+    }
+
+    #[test]
+    pub fn old_boot_test() {
+        let mut game = game(5);
+        let edge = shoals(&game)[0].1;
+        let corners = edge.get_corner_neighbors();
+        game.grid.corners.entry(corners[0]).or_default().settlement = Settlement::Settlement(0);
+        state(&mut game).old_boot = Some(0);
+        assert_eq!(scenarios::victory_points_to_win(&game, 0), 11);
+        assert_eq!(scenarios::victory_points_to_win(&game, 1), 10);
+
+        // Bob has fewer points than Alice, so he can't be given the boot.
+        assert!(fishermen::give_old_boot(&mut game, 0, 1).is_err());
+        assert!(fishermen::give_old_boot(&mut game, 1, 0).is_err());
+        game.grid.corners.entry(corners[1]).or_default().settlement = Settlement::Settlement(1);
+        fishermen::give_old_boot(&mut game, 0, 1).unwrap();
+        assert_eq!(scenarios::victory_points_to_win(&game, 1), 11);
+
+        // A victory point card that Bob has shown counts too.
+        game.players[1].visible_devcards.push(DevelopmentCard::VictoryPoint);
+        assert_eq!((game.victory_points(0), game.victory_points(1)), (1, 2));
+        assert!(fishermen::give_old_boot(&mut game, 1, 0).is_err());
+    }
+}
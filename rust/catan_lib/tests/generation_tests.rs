@@ -1,11 +1,16 @@
 #[cfg(test)]
 pub mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use serde_json;
     use catan_lib;
     use catan_lib::{GameGrid, types::{TileType, Resource}, configuration::MapGenerationSettings};
-    use catan_lib::types::Tile;
+    use catan_lib::types::{Tile, Port, PortResource};
+    use catan_lib::configuration::{PinnedTile, PinnedPort, NumberPlacement, ALPHABETICAL_NUMBERS};
+    use hexgrid::hex_coordinates::{self, EdgeDirection};
+    use hexgrid::{Grid, DenseHexGrid};
 
-    use catan_lib::generation::generate_tiles;
+    use catan_lib::generation::{generate_tiles, GenerationError};
 
     fn validate_counts(grid: &GameGrid, config: &MapGenerationSettings) {
         let mut wood_count = 0;
@@ -100,4 +105,170 @@ pub mod tests {
         let mut grid = catan_lib::generation::generate_tiles(&config).unwrap();
         catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn generate_dense_test() {
+        let config = MapGenerationSettings::defaults_seafarers();
+        let mut grid: DenseHexGrid<Tile, catan_lib::types::Edge, catan_lib::types::Corner> =
+            DenseHexGrid::bounding(&config.coords).unwrap();
+        catan_lib::generation::generate_tiles_into(&config, &mut grid).unwrap();
+        catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
+
+        let grid: GameGrid = grid.into();
+        validate_no_adjacent(&grid);
+        validate_counts(&grid, &config);
+    }
+
+    #[test]
+    pub fn unsatisfiable_tiles_test() {
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        config.avoid_adjacent = true;
+        config.wheat_count += config.wood_count + config.clay_count + config.stone_count + config.sheep_count;
+        config.wood_count = 0;
+        config.clay_count = 0;
+        config.stone_count = 0;
+        config.sheep_count = 0;
+        assert_eq!(generate_tiles(&config).unwrap_err(), GenerationError::Unsatisfiable);
+
+        let mut grid = GameGrid::new();
+        assert!(catan_lib::generation::generate_tiles_into(&config, &mut grid).is_err());
+        assert!(grid.tiles.is_empty());
+    }
+
+    #[test]
+    pub fn unsatisfiable_numbers_test() {
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        let mut grid = generate_tiles(&config).unwrap();
+        config.min_corner_score = 100;
+        config.max_corner_score = 100;
+        assert_eq!(
+            catan_lib::generation::generate_numbers(&config, &mut grid).unwrap_err(),
+            GenerationError::Unsatisfiable
+        );
+    }
+
+    #[test]
+    pub fn generate_seeded_test() {
+        let config = MapGenerationSettings::defaults_seafarers();
+        let grid = catan_lib::generation::generate_seeded(&config, 42).unwrap();
+        assert_eq!(catan_lib::generation::generate_seeded(&config, 42).unwrap(), grid);
+        validate_counts(&grid, &config);
+        validate_no_adjacent(&grid);
+
+        // Seeds are shared between players, so a seed has to give the same map everywhere, and
+        // after updating dependencies.
+        let grid = catan_lib::generation::generate_seeded(&MapGenerationSettings::defaults_vanilla(), 42).unwrap();
+        assert_eq!(
+            catan_lib::map_string::to_map_string(&grid).unwrap(),
+            "1.0.0.5.AAAAMDIFECAAQIEDMIUGUEMLIJQMEFEJULcAIEAAQGIKUKAAAA."
+        );
+    }
+
+    #[test]
+    pub fn generate_best_test() {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let score = |grid: &GameGrid| catan_lib::analysis::analyze(grid).balance_index;
+        let best = catan_lib::generation::generate_best(&config, 8, score).unwrap();
+        assert_eq!(best.score, score(&best.grid));
+        assert_eq!(catan_lib::generation::generate_seeded(&config, best.seed).unwrap(), best.grid);
+
+        let mut impossible = config.clone();
+        impossible.min_corner_score = 100;
+        impossible.max_corner_score = 100;
+        assert_eq!(
+            catan_lib::generation::generate_best(&impossible, 2, score).unwrap_err(),
+            GenerationError::Unsatisfiable
+        );
+
+        // NaN loses to every other score.
+        let calls = AtomicUsize::new(0);
+        let one_number = |_: &GameGrid| if calls.fetch_add(1, Ordering::Relaxed) == 2 { 1.0 } else { f64::NAN };
+        assert_eq!(catan_lib::generation::generate_best(&config, 5, one_number).unwrap().score, 1.0);
+        assert!(catan_lib::generation::generate_best(&config, 2, |_| f64::NAN).unwrap().score.is_nan());
+
+        assert!(matches!(
+            catan_lib::generation::generate_best(&config, 0, score),
+            Err(GenerationError::InvalidSettings(_))
+        ));
+    }
+
+    #[test]
+    pub fn pinned_test() {
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        let center = hex_coordinates::Tile::new(2, 2);
+        let corner = hex_coordinates::Tile::new(2, 0);
+        let port_edge = hex_coordinates::Edge::new(2, 0, EdgeDirection::Southwest);
+        let port = Port { resource_type: PortResource::One(Resource::Wheat), cost: 2, reward: 1 };
+        config.pinned_tiles = vec![
+            PinnedTile { coord: center, tile_type: TileType::Desert, number: None },
+            PinnedTile { coord: corner, tile_type: TileType::Resource(Resource::Wheat), number: Some(6) },
+        ];
+        config.pinned_ports = vec![PinnedPort { edge: port_edge, port }];
+
+        let mut grid = generate_tiles(&config).unwrap();
+        catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
+
+        assert_eq!(grid.tiles[&center].tile_type, TileType::Desert);
+        assert_eq!(grid.tiles[&corner].tile_type, TileType::Resource(Resource::Wheat));
+        assert_eq!(grid.tiles[&corner].number, Some(6));
+        assert_eq!(grid.edges[&port_edge].port, Some(port));
+        validate_counts(&grid, &config);
+        validate_no_adjacent(&grid);
+
+        let mut numbers: Vec<i32> = grid.tiles.values().filter_map(|t| t.number).collect();
+        numbers.sort();
+        assert_eq!(numbers, config.numbers);
+    }
+
+    #[test]
+    pub fn spiral_numbers_test() {
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        config.number_placement = NumberPlacement::Spiral {
+            start: Some(hex_coordinates::Tile::new(2, 0)),
+            sequence: ALPHABETICAL_NUMBERS.to_vec()
+        };
+        let mut grid = generate_tiles(&config).unwrap();
+        catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
+
+        // Counterclockwise around the outside, then the inside ring, then the center.
+        let path = [
+            (2, 0), (3, 0), (4, 0), (4, 1), (4, 2), (3, 3), (2, 4), (1, 4), (0, 4), (0, 3), (0, 2), (1, 1),
+            (2, 1), (3, 1), (3, 2), (2, 3), (1, 3), (1, 2),
+            (2, 2)
+        ];
+        let numbers: Vec<i32> = path.iter()
+            .filter_map(|(x, y)| grid.tiles[&hex_coordinates::Tile::new(*x, *y)].number)
+            .collect();
+        assert_eq!(numbers, ALPHABETICAL_NUMBERS.to_vec());
+
+        let json = r#"{"type": "Spiral"}"#;
+        let placement: NumberPlacement = serde_json::from_str(json).unwrap();
+        assert_eq!(placement, NumberPlacement::Spiral { start: None, sequence: ALPHABETICAL_NUMBERS.to_vec() });
+        config.number_placement = placement;
+        catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
+        let mut numbers: Vec<i32> = grid.tiles.values().filter_map(|t| t.number).collect();
+        numbers.sort();
+        assert_eq!(numbers, config.numbers);
+    }
+
+    #[test]
+    pub fn fixed_numbers_test() {
+        let mut config = MapGenerationSettings::defaults_vanilla();
+        let mut grid = generate_tiles(&config).unwrap();
+        let mut layout: HashMap<hex_coordinates::Tile, i32> = grid.tiles.iter()
+            .filter(|(_, t)| t.tile_type != TileType::Desert)
+            .map(|(c, _)| *c)
+            .zip(config.numbers.iter().cloned())
+            .collect();
+        config.number_placement = NumberPlacement::Fixed { numbers: layout.clone() };
+        catan_lib::generation::generate_numbers(&config, &mut grid).unwrap();
+        for (coord, number) in layout.iter() {
+            assert_eq!(grid.tiles[coord].number, Some(*number));
+        }
+
+        let missing = *layout.keys().next().unwrap();
+        layout.remove(&missing);
+        config.number_placement = NumberPlacement::Fixed { numbers: layout };
+        assert_eq!(catan_lib::generation::generate_numbers(&config, &mut grid), Err(GenerationError::Unsatisfiable));
+    }
+}
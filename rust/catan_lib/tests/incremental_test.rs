@@ -0,0 +1,50 @@
+#[cfg(test)]
+pub mod incremental_test {
+    use catan_lib::configuration::MapGenerationSettings;
+    use catan_lib::generation::{self, GenerationError};
+    use catan_lib::generation::incremental::{self, Budget, CancelToken, Generator, Phase};
+
+    #[test]
+    pub fn step_test() {
+        let config = MapGenerationSettings::defaults_seafarers();
+        let mut generator = Generator::seeded(config.clone(), 42).unwrap();
+        let mut phases = vec![generator.progress().phase];
+        while !generator.is_finished() {
+            let progress = generator.step(7).unwrap();
+            assert!(progress.placed <= progress.total);
+            if phases.last() != Some(&progress.phase) {
+                phases.push(progress.phase);
+            }
+        }
+        assert_eq!(phases, vec![Phase::Tiles, Phase::Numbers, Phase::Finished]);
+        assert_eq!(generator.into_grid(), generation::generate_seeded(&config, 42).unwrap());
+    }
+
+    #[test]
+    pub fn cancel_test() {
+        let mut generator = Generator::new(MapGenerationSettings::defaults_vanilla()).unwrap();
+        generator.step(1).unwrap();
+        generator.cancel_token().cancel();
+        assert_eq!(generator.step(1), Err(GenerationError::Cancelled));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let result = incremental::generate(&MapGenerationSettings::defaults_vanilla(), &Budget::default(), &cancel, |_| {});
+        assert_eq!(result, Err(GenerationError::Cancelled));
+    }
+
+    #[test]
+    pub fn budget_test() {
+        let config = MapGenerationSettings::defaults_vanilla();
+        let budget = Budget { max_steps: Some(10), max_time: None };
+        let mut reports = 0;
+        let result = incremental::generate(&config, &budget, &CancelToken::new(), |_| reports += 1);
+        assert_eq!(result, Err(GenerationError::OutOfBudget));
+        assert_eq!(reports, 1);
+
+        let mut last = None;
+        let grid = incremental::generate(&config, &Budget::default(), &CancelToken::new(), |p| last = Some(*p)).unwrap();
+        assert_eq!(grid.tiles.len(), 19);
+        assert_eq!(last.unwrap().phase, Phase::Finished);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+pub mod legacy_test {
+    use catan_lib::legacy;
+    use catan_lib::configuration::Rules;
+    use catan_lib::types::{TileType, Resource, Road, Settlement};
+    use hexgrid::hex_coordinates::{self, CornerDirection, EdgeDirection};
+
+    const BOARD: &str = include_str!("fixtures/legacy_board.json");
+
+    #[test]
+    pub fn import_board_test() {
+        let imported = legacy::import_board(BOARD).unwrap();
+        let grid = imported.value;
+
+        assert_eq!(grid.tiles.len(), 3);
+        let wheat = grid.tiles[&hex_coordinates::Tile::new(0, 0)];
+        assert_eq!(wheat.tile_type, TileType::Resource(Resource::Wheat));
+        assert_eq!(wheat.number, Some(6));
+        assert!(wheat.faceup);
+        let stone = grid.tiles[&hex_coordinates::Tile::new(1, 0)];
+        assert_eq!(stone.tile_type, TileType::Resource(Resource::Stone));
+        assert!(!stone.faceup);
+        let desert = grid.tiles[&hex_coordinates::Tile::new(0, 1)];
+        assert_eq!((desert.tile_type, desert.number, desert.thief), (TileType::Desert, None, true));
+
+        // Empty corners and edges aren't stored.
+        assert_eq!(grid.corners.len(), 2);
+        assert_eq!(
+            grid.corners[&hex_coordinates::Corner::new(0, 0, CornerDirection::Northeast)].settlement,
+            Settlement::City(7)
+        );
+        assert_eq!(
+            grid.corners[&hex_coordinates::Corner::new(1, 0, CornerDirection::North)].settlement,
+            Settlement::Settlement(9)
+        );
+        assert_eq!(grid.edges.len(), 2);
+        assert_eq!(grid.edges[&hex_coordinates::Edge::new(0, 0, EdgeDirection::East)].road, Road::Road(7));
+        assert_eq!(grid.edges[&hex_coordinates::Edge::new(2, -1, EdgeDirection::Northwest)].road, Road::Road(3));
+
+        assert_eq!(imported.warnings, vec![
+            "Skipped 0,0,Direction.EDGE_NW: ports can't be imported",
+            "Skipped the unknown field 'owner_name' at 1,0,Direction.CORNER_N",
+            "Skipped 1,1: the tile is face down, and its type was hidden when it was saved",
+        ]);
+    }
+
+    #[test]
+    pub fn import_game_test() {
+        let rules = Rules::defaults_vanilla();
+        let imported = legacy::import_game(5, &[7, 9], BOARD, &rules).unwrap();
+        let game = imported.value;
+
+        assert_eq!(game.id, 5);
+        assert_eq!(game.players.iter().map(|p| p.id()).collect::<Vec<_>>(), vec![7, 9]);
+        assert_ne!(game.players[0].color, game.players[1].color);
+        assert_eq!(game.players[0].cities, rules.city_count - 1);
+        assert_eq!(game.players[0].roads, rules.road_count - 1);
+        assert_eq!(game.players[1].settlements, rules.settlement_count - 1);
+        assert!(imported.warnings.contains(&"Player 3 has roads, but isn't in the game".to_string()));
+    }
+
+    #[test]
+    pub fn invalid_board_test() {
+        assert!(legacy::import_board("not json").is_err());
+        assert!(legacy::import_board("[]").is_err());
+        assert!(legacy::import_board(r#"{"corners": {}}"#).is_err());
+
+        let imported = legacy::import_board(r#"{"tiles": {"a,b": {}, "0,0": {"resourcetype": "lava"}}}"#).unwrap();
+        assert!(imported.value.tiles.is_empty());
+        assert_eq!(imported.warnings.len(), 2);
+    }
+}
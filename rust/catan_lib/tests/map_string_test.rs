@@ -0,0 +1,98 @@
+#[cfg(test)]
+pub mod map_string_test {
+    use catan_lib::{GameGrid, configuration::MapGenerationSettings, generation};
+    use catan_lib::types::{Edge, Port, PortResource, Resource, Road};
+    use catan_lib::map_string::{to_map_string, from_map_string};
+    use hexgrid::GridMut;
+    use hexgrid::hex_coordinates::{self, EdgeDirection};
+
+    fn generate(config: &MapGenerationSettings) -> GameGrid {
+        let mut grid = generation::generate_tiles(config).unwrap();
+        generation::generate_numbers(config, &mut grid).unwrap();
+        grid
+    }
+
+    fn assert_round_trip(grid: &GameGrid) {
+        let s = to_map_string(grid).unwrap();
+        let grid2 = from_map_string(&s).unwrap();
+        assert_eq!(serde_json::to_value(grid).unwrap(), serde_json::to_value(&grid2).unwrap());
+        assert_eq!(to_map_string(&grid2).unwrap(), s);
+    }
+
+    #[test]
+    pub fn round_trip_defaults_test() {
+        for config in &[
+            MapGenerationSettings::defaults_vanilla(),
+            MapGenerationSettings::defaults_vanilla56(),
+            MapGenerationSettings::defaults_seafarers(),
+        ] {
+            assert_round_trip(&generate(config));
+        }
+    }
+
+    #[test]
+    pub fn round_trip_flags_and_ports_test() {
+        let mut grid = generate(&MapGenerationSettings::defaults_vanilla());
+        let coords = hex_coordinates::Tile::new(2, 2);
+        grid.get_tile_mut(&coords).unwrap().thief = true;
+        grid.get_tile_mut(&hex_coordinates::Tile::new(2, 0)).unwrap().faceup = true;
+
+        grid.insert_edge(hex_coordinates::Edge::new(2, 0, EdgeDirection::Southwest), Edge {
+            port: Some(Port { resource_type: PortResource::Any, cost: 3, reward: 1 }),
+            road: Road::None,
+            shoal: None
+        });
+        grid.insert_edge(hex_coordinates::Edge::new(4, 2, EdgeDirection::East), Edge {
+            port: Some(Port { resource_type: PortResource::One(Resource::Sheep), cost: 2, reward: 1 }),
+            road: Road::None,
+            shoal: None
+        });
+
+        assert_round_trip(&grid);
+    }
+
+    #[test]
+    pub fn url_safe_test() {
+        let s = to_map_string(&generate(&MapGenerationSettings::defaults_seafarers())).unwrap();
+        assert!(s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)));
+    }
+
+    #[test]
+    pub fn empty_grid_test() {
+        assert_round_trip(&GameGrid::new());
+    }
+
+    #[test]
+    pub fn bad_string_test() {
+        let strings = &[
+            "",
+            "2.0.0.1.AA.",
+            "1.0.0.1.AA",
+            "1.0.0.0..",
+            "1.0.0.2.AAA.",
+            "1.x.0.1.AA.",
+            "1.0.0.1.A!.",
+            "1.0.0.1.AA.AAAAA",
+            "1.0.0.1.AA.AAAAAA.",
+        ];
+
+        for s in strings {
+            assert!(from_map_string(s).is_err(), "'{}' should not decode", s);
+        }
+    }
+
+    #[test]
+    pub fn out_of_range_coordinates_test() {
+        let strings = &[
+            "1.2147483647.0.2.EAEA.",
+            "1.0.2147483647.1.EAEA.",
+            "1.-2147483648.0.1.EA.AAAAAA",
+            "1.0.-2147483648.1.EA.AAAAAA",
+        ];
+
+        for s in strings {
+            assert!(from_map_string(s).is_err(), "'{}' should not decode", s);
+        }
+        assert!(from_map_string("1.2147483647.0.1.EA.").is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+pub mod player_test {
+    use catan_lib::configuration::Rules;
+    use catan_lib::player::{Player, Resources, Connection};
+    use catan_lib::types::{Resource, Commodity, Card};
+
+    #[test]
+    pub fn resources_test() {
+        let mut hand = Resources::of(Resource::Wheat, 2);
+        hand += &Resources::of(Resource::Stone, 3);
+        assert_eq!(hand.total(), 5);
+        assert_eq!(hand[Resource::Stone], 3);
+        assert_eq!(hand[Card::Resource(Resource::Stone)], 3);
+        assert_eq!(hand[Commodity::Coin], 0);
+        assert_eq!(
+            hand.iter().collect::<Vec<_>>(),
+            vec![(Card::Resource(Resource::Wheat), 2), (Card::Resource(Resource::Stone), 3)]
+        );
+
+        let price = Resources::of(Resource::Wheat, 1) + &Resources::of(Resource::Stone, 3);
+        assert!(hand.contains(&price));
+        assert!(!price.contains(&hand));
+        assert_eq!(hand.checked_sub(&price), Some(Resources::of(Resource::Wheat, 1)));
+        assert_eq!(price.checked_sub(&hand), None);
+        assert!(hand.checked_sub(&hand).unwrap().is_empty());
+        hand[Commodity::Paper] += 1;
+        assert_eq!(hand.total(), 6);
+        assert!(!price.contains(&Resources::of(Commodity::Paper, 1)));
+
+        let parsed: Resources = serde_json::from_str(r#"{"Wood": 1, "Clay": 1, "Cloth": 2}"#).unwrap();
+        let expected = Resources::of(Resource::Wood, 1) + &Resources::of(Resource::Clay, 1) + &Resources::of(Commodity::Cloth, 2);
+        assert_eq!(parsed, expected);
+        assert_eq!(serde_json::to_string(&expected).unwrap(), r#"{"Clay":1,"Wood":1,"Cloth":2}"#);
+        assert!(serde_json::from_str::<Resources>(r#"{"Lava": 1}"#).is_err());
+    }
+
+    #[test]
+    pub fn stats_test() {
+        let rules = Rules::defaults_vanilla();
+        let mut alice = Player::new(0, "Alice", 0xff0000, &rules);
+        let mut bob = Player::new(1, "Bob", 0x0000ff, &rules);
+
+        alice.receive(&Resources::of(Resource::Wood, 2));
+        bob.receive(&Resources::of(Resource::Sheep, 1));
+        bob.record_roll(7);
+        bob.record_roll(7);
+        bob.record_roll(13);
+        bob.steal(&mut alice, Resource::Wood).unwrap();
+        assert!(bob.steal(&mut alice, Resource::Clay).is_err());
+
+        assert_eq!(alice.resources, Resources::of(Resource::Wood, 1));
+        assert_eq!(alice.stats.resources_received, Resources::of(Resource::Wood, 2));
+        assert_eq!(alice.stats.cards_lost, 1);
+        assert_eq!(bob.resources, Resources::of(Resource::Sheep, 1) + &Resources::of(Resource::Wood, 1));
+        assert_eq!(bob.stats.resources_received, Resources::of(Resource::Sheep, 1));
+        assert_eq!(bob.stats.cards_stolen, 1);
+        assert_eq!(bob.stats.dice_rolls[7], 2);
+        assert_eq!(bob.stats.dice_rolls.iter().sum::<u32>(), 2);
+        assert_eq!(bob.connection, Connection::Disconnected);
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+pub mod render_test {
+    use catan_lib::GameGrid;
+    use catan_lib::types::{Tile, TileType, Resource, Edge, Road, Port, PortResource, Corner, Settlement};
+    use hexgrid::render::render_text;
+    use hexgrid::hex_coordinates::{self, EdgeDirection, CornerDirection};
+
+    #[test]
+    pub fn render_game_grid_test() {
+        let mut grid = GameGrid::new();
+        grid.tiles.insert(hex_coordinates::Tile::new(0, 0), Tile {
+            tile_type: TileType::Resource(Resource::Wheat),
+            number: Some(11),
+            thief: false,
+            faceup: true
+        });
+        grid.tiles.insert(hex_coordinates::Tile::new(1, 0), Tile {
+            tile_type: TileType::Desert,
+            number: None,
+            thief: true,
+            faceup: true
+        });
+
+        grid.edges.insert(hex_coordinates::Edge::new(0, 0, EdgeDirection::Northwest), Edge {
+            port: None,
+            road: Road::Road(1),
+            shoal: None,
+        });
+        grid.edges.insert(hex_coordinates::Edge::new(0, 0, EdgeDirection::West), Edge {
+            port: Some(Port { resource_type: PortResource::Any, cost: 3, reward: 1 }),
+            road: Road::None,
+            shoal: None,
+        });
+        grid.edges.insert(hex_coordinates::Edge::new(1, 0, EdgeDirection::Southeast), Edge {
+            port: None,
+            road: Road::Ship(2),
+            shoal: None,
+        });
+        grid.corners.insert(hex_coordinates::Corner::new(0, 0, CornerDirection::North), Corner {
+            settlement: Settlement::Settlement(1),
+            knight: None,
+        });
+        grid.corners.insert(hex_coordinates::Corner::new(1, 0, CornerDirection::South), Corner {
+            settlement: Settlement::City(30),
+            knight: None,
+        });
+
+        let expected = [
+            "    b       .",
+            "  1   \\   /   \\",
+            ".       .       .",
+            "@ Wh 11 |  De*  |",
+            ".       .       .",
+            "  \\   /   \\   ②",
+            "    .       ?",
+        ].join("\n");
+        assert_eq!(render_text(&grid), expected);
+    }
+}
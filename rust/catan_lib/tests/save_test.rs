@@ -0,0 +1,123 @@
+#[cfg(test)]
+pub mod save_test {
+    use catan_lib::{Game, GamePhase, save};
+    use catan_lib::configuration::Costs;
+    use catan_lib::dice::DiceMode;
+    use catan_lib::events::Event;
+    use catan_lib::player::{Resources, PlayerStats};
+    use catan_lib::types::{TileType, Resource};
+    use hexgrid::hex_coordinates;
+
+    #[test]
+    pub fn round_trip_test() {
+        let game = Game::generate_demo();
+        let json = save::to_json(&game).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], save::SAVE_VERSION);
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_1_test() {
+        let game = save::from_json(include_str!("fixtures/save_v1.json")).unwrap();
+        assert_eq!(game.id, 42);
+        assert_eq!(game.rules.victory_points, 10);
+        assert_eq!(game.players.len(), 2);
+        assert_eq!(game.players[0].resources[Resource::Wheat], 2);
+        assert_eq!(
+            game.grid.tiles[&hex_coordinates::Tile::new(0, 0)].tile_type,
+            TileType::Resource(Resource::Wheat)
+        );
+
+        // Saving it again upgrades it to the current version.
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_2_test() {
+        let game = save::from_json(include_str!("fixtures/save_v2.json")).unwrap();
+        assert_eq!(game.players[0].resources, Resources::of(Resource::Wheat, 2) + &Resources::of(Resource::Stone, 1));
+        assert_eq!(game.players[1].resources, Resources::of(Resource::Gold, 3));
+        assert_eq!(game.rules.costs, Costs::default());
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_3_test() {
+        let game = save::from_json(include_str!("fixtures/save_v3.json")).unwrap();
+        assert_eq!(game.phase, GamePhase::Playing);
+        assert_eq!(game.players[0].name, "Player 1");
+        assert_eq!(game.players[1].name, "Player 2");
+        assert_eq!(game.players[1].bot, None);
+        assert_eq!(game.players[1].resources, Resources::of(Resource::Gold, 3));
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_4_test() {
+        let game = save::from_json(include_str!("fixtures/save_v4.json")).unwrap();
+        assert_eq!(game.players[0].name, "Alice");
+        assert_eq!(game.players[0].bot, None);
+        assert_eq!(game.players[1].bot.as_deref(), Some("default"));
+        assert_eq!(game.players[0].account, None);
+        assert_eq!(game.players[0].stats, PlayerStats::default());
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_5_test() {
+        let json = include_str!("fixtures/save_v5.json");
+        let mut game = save::from_json(json).unwrap();
+        assert_eq!(game.players[0].account.as_deref(), Some("alice@example.com"));
+        assert_eq!(game.rules.dice, DiceMode::Deck { reshuffle_at: 5 });
+        assert!(game.events.is_empty());
+
+        // The same old save always rolls the same.
+        let first = game.roll_dice(1);
+        assert_eq!(save::from_json(json).unwrap().roll_dice(1), first);
+        assert_eq!(game.players[0].stats.dice_rolls[8], if first[0] + first[1] == 8 { 2 } else { 1 });
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_6_test() {
+        let json = include_str!("fixtures/save_v6.json");
+        let game = save::from_json(json).unwrap();
+        assert_eq!(game.events, vec![Event::Roll { player: 1, dice: [3, 5] }]);
+        assert_eq!(game.cities_and_knights, None);
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn version_7_test() {
+        let json = include_str!("fixtures/save_v7.json");
+        let game = save::from_json(json).unwrap();
+        assert_eq!(game.scenario, None);
+        assert_eq!(game.rules.scenario, None);
+        assert!(game.grid.edges.values().all(|e| e.shoal.is_none()));
+
+        let json = save::to_json(&game).unwrap();
+        assert_eq!(save::from_json(&json).unwrap(), game);
+    }
+
+    #[test]
+    pub fn invalid_save_test() {
+        let game = serde_json::to_value(Game::generate_demo()).unwrap();
+        let future = serde_json::json!({"version": save::SAVE_VERSION + 1, "game": game});
+        assert!(save::from_json(&future.to_string()).is_err());
+        assert!(save::from_json(r#"{"version": 0, "game": {}}"#).is_err());
+        assert!(save::from_json("{}").is_err());
+        assert!(save::from_json("not json").is_err());
+    }
+}
@@ -1,12 +1,24 @@
+use std::time::Duration;
 use yew::{html, Component, ComponentLink, Html, ShouldRender, Properties};
-use catan_lib::{Game, types, generation, configuration};
+use yew::services::TimeoutService;
+use yew::services::timeout::TimeoutTask;
+use catan_lib::{Game, types, configuration, map_string};
+use catan_lib::generation::incremental::{Generator, Phase};
 use serde_json;
 use super::grid_components::GridComponent;
 use log::debug;
 
+/// How many generation steps to take before letting the browser catch up.
+const STEPS_PER_UPDATE: usize = 200;
+
 pub struct GameComponent {
     link: ComponentLink<Self>,
-    game: Game
+    game: Game,
+    /// The map being generated, if there is one.
+    generator: Option<Generator>,
+    /// Keeps the timer for the next generation step alive.
+    step_task: Option<TimeoutTask>,
+    status: String
 }
 
 #[derive(Properties, PartialEq, Clone)]
@@ -19,6 +31,8 @@ pub enum GameMsg {
     RegenVanilla56,
     RegenSeafarers,
     RegenSheepland,
+    /// Carry on generating the map.
+    Step,
 }
 
 impl Component for GameComponent {
@@ -28,7 +42,10 @@ impl Component for GameComponent {
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         GameComponent {
             link,
-            game: props.game
+            game: props.game,
+            generator: None,
+            step_task: None,
+            status: String::new()
         }
     }
 
@@ -38,13 +55,21 @@ impl Component for GameComponent {
             GameMsg::RegenVanilla56 => configuration::MapGenerationSettings::defaults_vanilla56(),
             GameMsg::RegenSeafarers => configuration::MapGenerationSettings::defaults_seafarers(),
             GameMsg::RegenSheepland => configuration::MapGenerationSettings::defaults_sheepland(),
+            GameMsg::Step => return self.step(),
         };
 
-        let mut new_grid = generation::generate_tiles(&config).unwrap();
-        generation::generate_numbers(&config, &mut new_grid).unwrap();
-        debug!("New grid has {} tiles", new_grid.tiles.len());
-        self.game.grid = new_grid;
-        true
+        // Starting a new map replaces one that is still being generated.
+        match Generator::new(config) {
+            Ok(generator) => {
+                self.generator = Some(generator);
+                self.step()
+            },
+            Err(e) => {
+                self.generator = None;
+                self.status = format!("Can't generate this map: {:?}", e);
+                true
+            }
+        }
     }
 
     fn view(&self) -> Html {
@@ -55,6 +80,13 @@ impl Component for GameComponent {
         let callback_seafarers = self.link.callback(|_| GameMsg::RegenSeafarers);
         let callback_sheepland = self.link.callback(|_| GameMsg::RegenSheepland);
         let data = serde_json::to_string_pretty(&self.game.grid).unwrap();
+        let share_link = match map_string::to_map_string(&self.game.grid) {
+            Ok(s) => {
+                let link = format!("#{}", s);
+                html! { <a href=link.clone()>{ link }</a> }
+            },
+            Err(e) => html! { { format!("this map can't be shared ({})", e) } },
+        };
 
         html! {
         <>
@@ -62,8 +94,39 @@ impl Component for GameComponent {
             <button onclick=callback_vanilla56>{"Regenerate Vanilla56"}</button><br />
             <button onclick=callback_seafarers>{"Regenerate Seafarers"}</button><br />
             <button onclick=callback_sheepland>{"Regenerate the good map"}</button><br />
+            <p>{ &self.status }</p>
+            <p>{"Share this map: "}{ share_link }</p>
             <GridComponent grid=self.game.grid.clone() player_colors=self.game.get_player_colors() />
         </>
         }
     }
-}
\ No newline at end of file
+}
+
+impl GameComponent {
+    /// Take some generation steps, and then give the browser a chance to do other things before
+    /// taking some more.
+    fn step(&mut self) -> ShouldRender {
+        let generator = match self.generator.as_mut() {
+            Some(generator) => generator,
+            None => return false,
+        };
+        match generator.step(STEPS_PER_UPDATE) {
+            Ok(progress) if progress.phase == Phase::Finished => {
+                let new_grid = self.generator.take().unwrap().into_grid();
+                debug!("New grid has {} tiles", new_grid.tiles.len());
+                self.game.grid = new_grid;
+                self.status = String::new();
+            },
+            Ok(progress) => {
+                self.status = format!("Generating {:?}: {} of {} placed", progress.phase, progress.placed, progress.total);
+                let callback = self.link.callback(|_| GameMsg::Step);
+                self.step_task = Some(TimeoutService::new().spawn(Duration::from_millis(0), callback));
+            },
+            Err(e) => {
+                self.generator = None;
+                self.status = format!("Couldn't generate a map: {:?}", e);
+            }
+        }
+        true
+    }
+}
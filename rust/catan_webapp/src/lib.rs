@@ -2,7 +2,7 @@
 
 use wasm_bindgen::prelude::*;
 use yew::{html, Component, ComponentLink, Html, ShouldRender};
-use catan_lib::{Game, types, configuration};
+use catan_lib::{Game, types, configuration, map_string};
 use log::{Level, debug};
 
 pub mod game_component;
@@ -65,9 +65,19 @@ pub fn run_app() -> Result<(), JsValue> {
     console_log::init_with_level(Level::Debug);
     debug!("Set logger level to Debug");
 
+    // A map can be shared with a link like `index.html#1.0.0.5...`, using the map string format.
+    let mut game = Game::generate_demo();
+    let fragment = yew::utils::window().location().hash().unwrap_or_default();
+    if let Some(code) = fragment.strip_prefix('#').filter(|s| !s.is_empty()) {
+        match map_string::from_map_string(code) {
+            Ok(grid) => game.grid = grid,
+            Err(e) => debug!("Could not load map from URL: {}", e)
+        }
+    }
+
     //yew::start_app::<App>();
     yew::start_app_with_props::<game_component::GameComponent>(game_component::GameProps {
-        game: Game::generate_demo()
+        game
     });
 
     Ok(())
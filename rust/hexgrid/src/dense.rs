@@ -0,0 +1,257 @@
+//! A bounded hex grid, stored in flat vectors instead of hash maps.
+//!
+//! This is much faster than `HexGrid` for lookups, at the cost of having to know the size of the
+//! grid up front. It is intended for code that looks up neighbors in a tight loop, like map
+//! generation and AI simulations.
+
+use super::{Grid, GridMut, HexGrid};
+use super::hex_coordinates::{Tile, Edge, Corner, CanonicalEdgeDir, CanonicalCornerDir};
+
+const EDGES_PER_TILE: usize = 3;
+const CORNERS_PER_TILE: usize = 2;
+
+/// A hex grid covering every tile from `min` to `max` (inclusive) in both x and y.
+///
+/// Coordinates are stored relative to `min`, so the grid does not need to start at (0, 0).
+/// Edges and corners are stored with one extra tile of margin on every side, because the edges
+/// and corners of a tile on the border can have coordinates outside of the tile bounds.
+///
+/// Getting data from outside the bounds returns `None`, like a missing key in a `HexGrid`.
+/// Inserting data outside the bounds panics.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DenseHexGrid<T, E, C> {
+    min: Tile,
+    max: Tile,
+    tiles: Vec<Option<T>>,
+    edges: Vec<Option<E>>,
+    corners: Vec<Option<C>>
+}
+
+impl<T, E, C> DenseHexGrid<T, E, C> {
+    pub fn new(min: Tile, max: Tile) -> Self {
+        assert!(min.x <= max.x && min.y <= max.y, "Min bound {} is bigger than max bound {}", min, max);
+        let tile_count = ((max.x - min.x + 1) * (max.y - min.y + 1)) as usize;
+        let border_count = ((max.x - min.x + 3) * (max.y - min.y + 3)) as usize;
+
+        DenseHexGrid {
+            min,
+            max,
+            tiles: (0..tile_count).map(|_| None).collect(),
+            edges: (0..border_count * EDGES_PER_TILE).map(|_| None).collect(),
+            corners: (0..border_count * CORNERS_PER_TILE).map(|_| None).collect(),
+        }
+    }
+
+    /// Create an empty grid just big enough to hold all of the given tiles.
+    ///
+    /// Returns `None` if `coords` is empty.
+    pub fn bounding(coords: &[Tile]) -> Option<Self> {
+        let min_x = coords.iter().map(|c| c.x).min()?;
+        let max_x = coords.iter().map(|c| c.x).max()?;
+        let min_y = coords.iter().map(|c| c.y).min()?;
+        let max_y = coords.iter().map(|c| c.y).max()?;
+        Some(Self::new(Tile::new(min_x, min_y), Tile::new(max_x, max_y)))
+    }
+
+    pub fn min(&self) -> Tile {
+        self.min
+    }
+
+    pub fn max(&self) -> Tile {
+        self.max
+    }
+
+    fn tile_width(&self) -> i32 {
+        self.max.x - self.min.x + 1
+    }
+
+    fn border_width(&self) -> i32 {
+        self.max.x - self.min.x + 3
+    }
+
+    fn tile_index(&self, coords: &Tile) -> Option<usize> {
+        if coords.x < self.min.x || coords.x > self.max.x || coords.y < self.min.y || coords.y > self.max.y {
+            return None;
+        }
+        Some(((coords.y - self.min.y) * self.tile_width() + (coords.x - self.min.x)) as usize)
+    }
+
+    /// Index of the tile position that these edge or corner coordinates are stored under.
+    fn border_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < self.min.x - 1 || x > self.max.x + 1 || y < self.min.y - 1 || y > self.max.y + 1 {
+            return None;
+        }
+        Some(((y - self.min.y + 1) * self.border_width() + (x - self.min.x + 1)) as usize)
+    }
+
+    fn edge_index(&self, coords: &Edge) -> Option<usize> {
+        let dir = match coords.dir {
+            CanonicalEdgeDir::Northwest => 0,
+            CanonicalEdgeDir::Northeast => 1,
+            CanonicalEdgeDir::East => 2,
+        };
+        self.border_index(coords.x, coords.y).map(|i| i * EDGES_PER_TILE + dir)
+    }
+
+    fn corner_index(&self, coords: &Corner) -> Option<usize> {
+        let dir = match coords.dir {
+            CanonicalCornerDir::North => 0,
+            CanonicalCornerDir::Northeast => 1,
+        };
+        self.border_index(coords.x, coords.y).map(|i| i * CORNERS_PER_TILE + dir)
+    }
+}
+
+impl<T, E, C> Grid for DenseHexGrid<T, E, C> {
+    type TileData = T;
+    type EdgeData = E;
+    type CornerData = C;
+
+    fn get_tile(&self, coords: &Tile) -> Option<&T> {
+        self.tile_index(coords).and_then(|i| self.tiles[i].as_ref())
+    }
+
+    fn get_edge(&self, coords: &Edge) -> Option<&E> {
+        self.edge_index(coords).and_then(|i| self.edges[i].as_ref())
+    }
+
+    fn get_corner(&self, coords: &Corner) -> Option<&C> {
+        self.corner_index(coords).and_then(|i| self.corners[i].as_ref())
+    }
+
+    fn iter_tiles(&self) -> Box<dyn Iterator<Item = (Tile, &T)> + '_> {
+        let (min, width) = (self.min, self.tile_width());
+        Box::new(self.tiles.iter().enumerate()
+            .filter_map(move |(i, data)| data.as_ref().map(|data| (tile_at(min, width, i), data))))
+    }
+
+    fn iter_edges(&self) -> Box<dyn Iterator<Item = (Edge, &E)> + '_> {
+        let (min, width) = (self.min, self.border_width());
+        Box::new(self.edges.iter().enumerate()
+            .filter_map(move |(i, data)| data.as_ref().map(|data| (edge_at(min, width, i), data))))
+    }
+
+    fn iter_corners(&self) -> Box<dyn Iterator<Item = (Corner, &C)> + '_> {
+        let (min, width) = (self.min, self.border_width());
+        Box::new(self.corners.iter().enumerate()
+            .filter_map(move |(i, data)| data.as_ref().map(|data| (corner_at(min, width, i), data))))
+    }
+}
+
+impl<T, E, C> GridMut for DenseHexGrid<T, E, C> {
+    fn get_tile_mut(&mut self, coords: &Tile) -> Option<&mut T> {
+        let i = self.tile_index(coords)?;
+        self.tiles[i].as_mut()
+    }
+
+    fn get_edge_mut(&mut self, coords: &Edge) -> Option<&mut E> {
+        let i = self.edge_index(coords)?;
+        self.edges[i].as_mut()
+    }
+
+    fn get_corner_mut(&mut self, coords: &Corner) -> Option<&mut C> {
+        let i = self.corner_index(coords)?;
+        self.corners[i].as_mut()
+    }
+
+    fn insert_tile(&mut self, coords: Tile, data: T) -> Option<T> {
+        let i = self.tile_index(&coords)
+            .unwrap_or_else(|| panic!("Tile {} is outside of the grid bounds", coords));
+        self.tiles[i].replace(data)
+    }
+
+    fn insert_edge(&mut self, coords: Edge, data: E) -> Option<E> {
+        let i = self.edge_index(&coords)
+            .unwrap_or_else(|| panic!("Edge {} is outside of the grid bounds", coords));
+        self.edges[i].replace(data)
+    }
+
+    fn insert_corner(&mut self, coords: Corner, data: C) -> Option<C> {
+        let i = self.corner_index(&coords)
+            .unwrap_or_else(|| panic!("Corner {} is outside of the grid bounds", coords));
+        self.corners[i].replace(data)
+    }
+
+    fn remove_tile(&mut self, coords: &Tile) -> Option<T> {
+        let i = self.tile_index(coords)?;
+        self.tiles[i].take()
+    }
+
+    fn remove_edge(&mut self, coords: &Edge) -> Option<E> {
+        let i = self.edge_index(coords)?;
+        self.edges[i].take()
+    }
+
+    fn remove_corner(&mut self, coords: &Corner) -> Option<C> {
+        let i = self.corner_index(coords)?;
+        self.corners[i].take()
+    }
+}
+
+impl<T, E, C> From<HexGrid<T, E, C>> for DenseHexGrid<T, E, C> {
+    /// Convert a `HexGrid` into a `DenseHexGrid` just big enough to hold all of its data.
+    ///
+    /// An empty `HexGrid` becomes a grid containing only the tile (0, 0).
+    fn from(grid: HexGrid<T, E, C>) -> Self {
+        let coords: Vec<Tile> = grid.tiles.keys().cloned()
+            .chain(grid.edges.keys().map(|e| Tile::new(e.x, e.y)))
+            .chain(grid.corners.keys().map(|c| Tile::new(c.x, c.y)))
+            .collect();
+        let mut dense = Self::bounding(&coords).unwrap_or_else(|| Self::new(Tile::new(0, 0), Tile::new(0, 0)));
+
+        for (coords, data) in grid.tiles {
+            dense.insert_tile(coords, data);
+        }
+        for (coords, data) in grid.edges {
+            dense.insert_edge(coords, data);
+        }
+        for (coords, data) in grid.corners {
+            dense.insert_corner(coords, data);
+        }
+        dense
+    }
+}
+
+impl<T, E, C> From<DenseHexGrid<T, E, C>> for HexGrid<T, E, C> {
+    fn from(dense: DenseHexGrid<T, E, C>) -> Self {
+        let (min, tile_width, border_width) = (dense.min, dense.tile_width(), dense.border_width());
+        HexGrid {
+            tiles: dense.tiles.into_iter().enumerate()
+                .filter_map(|(i, data)| data.map(|data| (tile_at(min, tile_width, i), data)))
+                .collect(),
+            edges: dense.edges.into_iter().enumerate()
+                .filter_map(|(i, data)| data.map(|data| (edge_at(min, border_width, i), data)))
+                .collect(),
+            corners: dense.corners.into_iter().enumerate()
+                .filter_map(|(i, data)| data.map(|data| (corner_at(min, border_width, i), data)))
+                .collect(),
+        }
+    }
+}
+
+// These are the inverses of the `_index` functions above. They are free functions, rather than
+// methods, so that they can be used while the vectors are being consumed.
+
+fn tile_at(min: Tile, width: i32, i: usize) -> Tile {
+    let i = i as i32;
+    Tile::new(min.x + i % width, min.y + i / width)
+}
+
+fn edge_at(min: Tile, border_width: i32, i: usize) -> Edge {
+    let dir = match i % EDGES_PER_TILE {
+        0 => CanonicalEdgeDir::Northwest,
+        1 => CanonicalEdgeDir::Northeast,
+        _ => CanonicalEdgeDir::East,
+    };
+    let i = (i / EDGES_PER_TILE) as i32;
+    Edge { x: min.x - 1 + i % border_width, y: min.y - 1 + i / border_width, dir }
+}
+
+fn corner_at(min: Tile, border_width: i32, i: usize) -> Corner {
+    let dir = match i % CORNERS_PER_TILE {
+        0 => CanonicalCornerDir::North,
+        _ => CanonicalCornerDir::Northeast,
+    };
+    let i = (i / CORNERS_PER_TILE) as i32;
+    Corner { x: min.x - 1 + i % border_width, y: min.y - 1 + i / border_width, dir }
+}
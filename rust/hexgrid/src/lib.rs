@@ -1,10 +1,73 @@
 pub mod hex_coordinates;
+pub mod region;
+pub mod dense;
+pub mod render;
 //pub mod demo;
 
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use hex_coordinates::{HexCoord, Tile, Edge, Corner};
 
+pub use dense::DenseHexGrid;
+
+/// Read access to the data stored on a hex grid, shared by every way of storing one.
+///
+/// Code that only needs to look at a grid should take a `Grid` rather than a `HexGrid`, so it
+/// can run on any storage. The neighbor functions are built on top of the `get_` functions, so
+/// they behave the same no matter how the grid is stored.
+pub trait Grid {
+    type TileData;
+    type EdgeData;
+    type CornerData;
+
+    fn get_tile(&self, coords: &Tile) -> Option<&Self::TileData>;
+    fn get_edge(&self, coords: &Edge) -> Option<&Self::EdgeData>;
+    fn get_corner(&self, coords: &Corner) -> Option<&Self::CornerData>;
+
+    /// Iterate over every tile that has data, in no particular order.
+    fn iter_tiles(&self) -> Box<dyn Iterator<Item = (Tile, &Self::TileData)> + '_>;
+    /// Iterate over every edge that has data, in no particular order.
+    fn iter_edges(&self) -> Box<dyn Iterator<Item = (Edge, &Self::EdgeData)> + '_>;
+    /// Iterate over every corner that has data, in no particular order.
+    fn iter_corners(&self) -> Box<dyn Iterator<Item = (Corner, &Self::CornerData)> + '_>;
+
+    fn get_tile_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Tile, &Self::TileData)> {
+        coords.get_tile_neighbors().into_iter()
+            .filter_map(|c| self.get_tile(&c).map(|data| (c, data)))
+            .collect()
+    }
+
+    fn get_corner_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Corner, &Self::CornerData)> {
+        coords.get_corner_neighbors().into_iter()
+            .filter_map(|c| self.get_corner(&c).map(|data| (c, data)))
+            .collect()
+    }
+
+    fn get_edge_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Edge, &Self::EdgeData)> {
+        coords.get_edge_neighbors().into_iter()
+            .filter_map(|c| self.get_edge(&c).map(|data| (c, data)))
+            .collect()
+    }
+}
+
+/// Write access to the data stored on a hex grid.
+///
+/// The `insert_` functions return the data that was previously at those coordinates, if any.
+/// Grids with fixed bounds (like `DenseHexGrid`) may panic when inserting outside of them.
+pub trait GridMut: Grid {
+    fn get_tile_mut(&mut self, coords: &Tile) -> Option<&mut Self::TileData>;
+    fn get_edge_mut(&mut self, coords: &Edge) -> Option<&mut Self::EdgeData>;
+    fn get_corner_mut(&mut self, coords: &Corner) -> Option<&mut Self::CornerData>;
+
+    fn insert_tile(&mut self, coords: Tile, data: Self::TileData) -> Option<Self::TileData>;
+    fn insert_edge(&mut self, coords: Edge, data: Self::EdgeData) -> Option<Self::EdgeData>;
+    fn insert_corner(&mut self, coords: Corner, data: Self::CornerData) -> Option<Self::CornerData>;
+
+    fn remove_tile(&mut self, coords: &Tile) -> Option<Self::TileData>;
+    fn remove_edge(&mut self, coords: &Edge) -> Option<Self::EdgeData>;
+    fn remove_corner(&mut self, coords: &Corner) -> Option<Self::CornerData>;
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct HexGrid<T, E, C> {
     pub tiles: HashMap<Tile, T>,
@@ -20,25 +83,72 @@ impl<T, E, C> HexGrid<T, E, C> {
             corners: HashMap::new()
         }
     }
+}
 
-    pub fn get_tile_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Tile, &T)> {
-        coords.get_tile_neighbors().into_iter()
-            .map(|c| self.tiles.get(&c).map(|data| (c, data)))
-            .flatten()
-            .collect()
+impl<T, E, C> Grid for HexGrid<T, E, C> {
+    type TileData = T;
+    type EdgeData = E;
+    type CornerData = C;
+
+    fn get_tile(&self, coords: &Tile) -> Option<&T> {
+        self.tiles.get(coords)
     }
 
-    pub fn get_corner_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Corner, &C)> {
-        coords.get_corner_neighbors().into_iter()
-            .map(|c| self.corners.get(&c).map(|data| (c, data)))
-            .flatten()
-            .collect()
+    fn get_edge(&self, coords: &Edge) -> Option<&E> {
+        self.edges.get(coords)
     }
 
-    pub fn get_edge_neighbors<CoordType: HexCoord>(&self, coords: &CoordType) -> Vec<(Edge, &E)> {
-        coords.get_edge_neighbors().into_iter()
-            .map(|c| self.edges.get(&c).map(|data| (c, data)))
-            .flatten()
-            .collect()
+    fn get_corner(&self, coords: &Corner) -> Option<&C> {
+        self.corners.get(coords)
+    }
+
+    fn iter_tiles(&self) -> Box<dyn Iterator<Item = (Tile, &T)> + '_> {
+        Box::new(self.tiles.iter().map(|(c, data)| (*c, data)))
+    }
+
+    fn iter_edges(&self) -> Box<dyn Iterator<Item = (Edge, &E)> + '_> {
+        Box::new(self.edges.iter().map(|(c, data)| (*c, data)))
+    }
+
+    fn iter_corners(&self) -> Box<dyn Iterator<Item = (Corner, &C)> + '_> {
+        Box::new(self.corners.iter().map(|(c, data)| (*c, data)))
+    }
+}
+
+impl<T, E, C> GridMut for HexGrid<T, E, C> {
+    fn get_tile_mut(&mut self, coords: &Tile) -> Option<&mut T> {
+        self.tiles.get_mut(coords)
+    }
+
+    fn get_edge_mut(&mut self, coords: &Edge) -> Option<&mut E> {
+        self.edges.get_mut(coords)
+    }
+
+    fn get_corner_mut(&mut self, coords: &Corner) -> Option<&mut C> {
+        self.corners.get_mut(coords)
     }
-}
\ No newline at end of file
+
+    fn insert_tile(&mut self, coords: Tile, data: T) -> Option<T> {
+        self.tiles.insert(coords, data)
+    }
+
+    fn insert_edge(&mut self, coords: Edge, data: E) -> Option<E> {
+        self.edges.insert(coords, data)
+    }
+
+    fn insert_corner(&mut self, coords: Corner, data: C) -> Option<C> {
+        self.corners.insert(coords, data)
+    }
+
+    fn remove_tile(&mut self, coords: &Tile) -> Option<T> {
+        self.tiles.remove(coords)
+    }
+
+    fn remove_edge(&mut self, coords: &Edge) -> Option<E> {
+        self.edges.remove(coords)
+    }
+
+    fn remove_corner(&mut self, coords: &Corner) -> Option<C> {
+        self.corners.remove(coords)
+    }
+}
@@ -0,0 +1,161 @@
+//! Queries about a region of a `HexGrid`, where a region is the set of tiles whose data matches
+//! some predicate (for example, "every tile that isn't ocean").
+//!
+//! Tiles that are not stored in the grid at all are always considered to be outside the region.
+
+use std::collections::{HashSet, VecDeque};
+use super::HexGrid;
+use super::hex_coordinates::{HexCoord, Tile, Edge, Corner};
+
+/// One closed loop of edges around a region.
+///
+/// Consecutive edges share a corner, and the last edge shares a corner with the first one.
+/// `corners[i]` is the corner between `edges[i]` and `edges[(i + 1) % edges.len()]`, so both
+/// lists always have the same length.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Perimeter {
+    pub edges: Vec<Edge>,
+    pub corners: Vec<Corner>,
+    /// True if this loop runs around a hole in the region, rather than around its outside.
+    pub hole: bool
+}
+
+impl<T, E, C> HexGrid<T, E, C> {
+    /// True if the tile at these coordinates exists and matches the predicate.
+    fn tile_in_region<F: Fn(&T) -> bool>(&self, tile: &Tile, in_region: &F) -> bool {
+        self.tiles.get(tile).map_or(false, in_region)
+    }
+
+    /// Get every edge with a tile in the region on one side, and a tile outside the region on
+    /// the other side. For example, with `|t| t.tile_type != TileType::Ocean`, this is the
+    /// coastline.
+    pub fn boundary_edges<F: Fn(&T) -> bool>(&self, in_region: F) -> Vec<Edge> {
+        self.boundary_edge_set(&in_region).into_iter().collect()
+    }
+
+    /// Get every corner touching at least one boundary edge.
+    pub fn boundary_corners<F: Fn(&T) -> bool>(&self, in_region: F) -> Vec<Corner> {
+        let corners: HashSet<Corner> = self.boundary_edge_set(&in_region)
+            .iter()
+            .flat_map(|edge| edge.get_corner_neighbors())
+            .collect();
+        corners.into_iter().collect()
+    }
+
+    /// Split the boundary of the region into closed loops. Each separate island gets its own
+    /// outer loop, and each hole in an island (such as a lake) gets its own loop with
+    /// `hole` set to true.
+    pub fn perimeters<F: Fn(&T) -> bool>(&self, in_region: F) -> Vec<Perimeter> {
+        let boundary = self.boundary_edge_set(&in_region);
+        let hole_tiles: HashSet<Tile> = self.holes(&in_region).into_iter().flatten().collect();
+        let mut remaining = boundary.clone();
+        let mut perimeters = Vec::new();
+
+        while let Some(&start) = remaining.iter().next() {
+            remaining.remove(&start);
+            let mut edges = vec![start];
+            let mut corners = Vec::new();
+            let mut current = start;
+            let mut corner = start.get_corner_neighbors()[1];
+
+            // Every corner on the boundary has exactly two boundary edges, because only three
+            // tiles meet at a corner. So there is never any choice about which way to go.
+            loop {
+                corners.push(corner);
+                let next = corner.get_edge_neighbors()
+                    .into_iter()
+                    .find(|edge| *edge != current && boundary.contains(edge))
+                    .expect("Boundary corner should have exactly two boundary edges");
+                if next == start {
+                    break;
+                }
+                remaining.remove(&next);
+                edges.push(next);
+
+                let next_corners = next.get_corner_neighbors();
+                corner = if next_corners[0] == corner { next_corners[1] } else { next_corners[0] };
+                current = next;
+            }
+
+            let hole = start.get_tile_neighbors()
+                .iter()
+                .any(|tile| hole_tiles.contains(tile));
+            perimeters.push(Perimeter { edges, corners, hole });
+        }
+
+        perimeters
+    }
+
+    /// Get the tiles that are outside the region, but completely surrounded by it, grouped into
+    /// connected pieces. Missing tiles count as outside the region, so a gap in the grid that is
+    /// surrounded by the region is also a hole.
+    pub fn holes<F: Fn(&T) -> bool>(&self, in_region: F) -> Vec<Vec<Tile>> {
+        let region: Vec<&Tile> = self.tiles.iter()
+            .filter(|(_, data)| in_region(data))
+            .map(|(coord, _)| coord)
+            .collect();
+        if region.is_empty() {
+            return Vec::new();
+        }
+
+        // Anything outside this box (which has a one tile margin around the region) is
+        // definitely not in a hole, so flood fill the outside starting from the edge of the box.
+        let min_x = region.iter().map(|t| t.x).min().unwrap() - 1;
+        let max_x = region.iter().map(|t| t.x).max().unwrap() + 1;
+        let min_y = region.iter().map(|t| t.y).min().unwrap() - 1;
+        let max_y = region.iter().map(|t| t.y).max().unwrap() + 1;
+        let in_box = |t: &Tile| t.x >= min_x && t.x <= max_x && t.y >= min_y && t.y <= max_y;
+        let outside = |t: &Tile| in_box(t) && !self.tile_in_region(t, &in_region);
+
+        let mut reached: HashSet<Tile> = HashSet::new();
+        let mut queue: VecDeque<Tile> = VecDeque::new();
+        for x in min_x..=max_x {
+            queue.push_back(Tile::new(x, min_y));
+            queue.push_back(Tile::new(x, max_y));
+        }
+        for y in min_y..=max_y {
+            queue.push_back(Tile::new(min_x, y));
+            queue.push_back(Tile::new(max_x, y));
+        }
+        flood_fill(&mut queue, &mut reached, &outside);
+
+        let mut holes = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let tile = Tile::new(x, y);
+                if outside(&tile) && !reached.contains(&tile) {
+                    let mut hole = HashSet::new();
+                    queue.push_back(tile);
+                    flood_fill(&mut queue, &mut hole, &outside);
+                    reached.extend(hole.iter());
+                    holes.push(hole.into_iter().collect());
+                }
+            }
+        }
+
+        holes
+    }
+
+    fn boundary_edge_set<F: Fn(&T) -> bool>(&self, in_region: &F) -> HashSet<Edge> {
+        self.tiles.iter()
+            .filter(|(_, data)| in_region(data))
+            .flat_map(|(coord, _)| coord.get_edge_neighbors())
+            .filter(|edge| {
+                edge.get_tile_neighbors()
+                    .iter()
+                    .any(|tile| !self.tile_in_region(tile, in_region))
+            })
+            .collect()
+    }
+}
+
+/// Breadth-first search from everything in `queue`, adding every tile that matches `include`
+/// to `reached`.
+fn flood_fill<F: Fn(&Tile) -> bool>(queue: &mut VecDeque<Tile>, reached: &mut HashSet<Tile>, include: &F) {
+    while let Some(tile) = queue.pop_front() {
+        if !include(&tile) || !reached.insert(tile) {
+            continue;
+        }
+        queue.extend(tile.get_tile_neighbors());
+    }
+}
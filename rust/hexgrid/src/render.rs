@@ -0,0 +1,155 @@
+//! Draw a hex grid as text, for debugging and for readable test assertions.
+//!
+//! Each tile is drawn like this, with its label in the middle:
+//!
+//! ```text
+//!     .
+//!   /   \
+//! .       .
+//! | Wh 8  |
+//! .       .
+//!   \   /
+//!     .
+//! ```
+//!
+//! Edges and corners with data are drawn with their glyph in place of the outline.
+
+use super::Grid;
+use super::hex_coordinates::{Tile, Edge, Corner, CanonicalEdgeDir, CanonicalCornerDir};
+
+/// The most characters that fit inside a tile.
+pub const TILE_LABEL_WIDTH: usize = 7;
+
+/// How to draw a piece of data as text. Works a bit like `Display`.
+///
+/// Tiles are drawn centered, and cut off after `TILE_LABEL_WIDTH` characters. Edges and corners
+/// only have room for one character, so only the first character is drawn. An empty string
+/// means to draw the plain outline instead.
+pub trait Glyph {
+    fn glyph(&self) -> String;
+}
+
+impl Glyph for () {
+    fn glyph(&self) -> String {
+        String::new()
+    }
+}
+
+impl Glyph for char {
+    fn glyph(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Glyph for &str {
+    fn glyph(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Glyph for String {
+    fn glyph(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<T: Glyph> Glyph for Option<T> {
+    fn glyph(&self) -> String {
+        self.as_ref().map(Glyph::glyph).unwrap_or_default()
+    }
+}
+
+/// Draw every tile, edge, and corner in the grid. Lines have no trailing whitespace, and there
+/// is no trailing newline.
+pub fn render_text<G>(grid: &G) -> String
+where
+    G: Grid,
+    G::TileData: Glyph,
+    G::EdgeData: Glyph,
+    G::CornerData: Glyph
+{
+    let tiles: Vec<(Tile, &G::TileData)> = grid.iter_tiles().collect();
+    let mut chars: Vec<(i32, i32, char)> = Vec::new();
+
+    // Later characters are drawn over earlier ones, so the outlines go first.
+    for (tile, _) in tiles.iter() {
+        chars.extend(tile_outline(tile));
+    }
+    for (edge, data) in grid.iter_edges() {
+        if let Some(c) = data.glyph().chars().next() {
+            let (x, y) = edge_position(&edge);
+            chars.push((x, y, c));
+        }
+    }
+    for (corner, data) in grid.iter_corners() {
+        if let Some(c) = data.glyph().chars().next() {
+            let (x, y) = corner_position(&corner);
+            chars.push((x, y, c));
+        }
+    }
+    for (tile, data) in tiles.iter() {
+        let label: Vec<char> = data.glyph().chars().take(TILE_LABEL_WIDTH).collect();
+        let (cx, cy) = center(tile.x, tile.y);
+        let start = cx - 3 + (TILE_LABEL_WIDTH - label.len()) as i32 / 2;
+        chars.extend(label.into_iter().enumerate().map(|(i, c)| (start + i as i32, cy, c)));
+    }
+
+    if chars.is_empty() {
+        return String::new();
+    }
+    let min_x = chars.iter().map(|c| c.0).min().unwrap();
+    let max_x = chars.iter().map(|c| c.0).max().unwrap();
+    let min_y = chars.iter().map(|c| c.1).min().unwrap();
+    let max_y = chars.iter().map(|c| c.1).max().unwrap();
+
+    let mut canvas = vec![vec![' '; (max_x - min_x + 1) as usize]; (max_y - min_y + 1) as usize];
+    for (x, y, c) in chars {
+        canvas[(y - min_y) as usize][(x - min_x) as usize] = c;
+    }
+
+    canvas.into_iter()
+        .map(|line| line.into_iter().collect::<String>().trim_end().to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Position of the middle of a tile in characters. Each tile is 8 characters wide, and each row
+/// of tiles is 4 lines below the last. Up on the screen is north, which is increasing y.
+fn center(x: i32, y: i32) -> (i32, i32) {
+    (8 * x + 4 * y, -4 * y)
+}
+
+fn edge_position(edge: &Edge) -> (i32, i32) {
+    let (cx, cy) = center(edge.x, edge.y);
+    match edge.dir {
+        CanonicalEdgeDir::Northwest => (cx - 2, cy - 2),
+        CanonicalEdgeDir::Northeast => (cx + 2, cy - 2),
+        CanonicalEdgeDir::East => (cx + 4, cy),
+    }
+}
+
+fn corner_position(corner: &Corner) -> (i32, i32) {
+    let (cx, cy) = center(corner.x, corner.y);
+    match corner.dir {
+        CanonicalCornerDir::North => (cx, cy - 3),
+        CanonicalCornerDir::Northeast => (cx + 4, cy - 1),
+    }
+}
+
+fn tile_outline(tile: &Tile) -> Vec<(i32, i32, char)> {
+    let (cx, cy) = center(tile.x, tile.y);
+    vec![
+        (cx, cy - 3, '.'),
+        (cx - 2, cy - 2, '/'),
+        (cx + 2, cy - 2, '\\'),
+        (cx - 4, cy - 1, '.'),
+        (cx + 4, cy - 1, '.'),
+        (cx - 4, cy, '|'),
+        (cx + 4, cy, '|'),
+        (cx - 4, cy + 1, '.'),
+        (cx + 4, cy + 1, '.'),
+        (cx - 2, cy + 2, '\\'),
+        (cx + 2, cy + 2, '/'),
+        (cx, cy + 3, '.'),
+    ]
+}
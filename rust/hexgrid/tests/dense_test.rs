@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod dense_tests {
+    use hexgrid::{HexGrid, DenseHexGrid, Grid, GridMut};
+    use hexgrid::hex_coordinates::{HexCoord, Tile, Corner, CornerDirection, Edge, EdgeDirection};
+
+    #[test]
+    pub fn insert_get_remove_test() {
+        let mut grid: DenseHexGrid<i32, &str, bool> = DenseHexGrid::new(Tile::new(-2, -2), Tile::new(2, 2));
+
+        assert_eq!(grid.insert_tile(Tile::new(1, -1), 5), None);
+        assert_eq!(grid.insert_tile(Tile::new(1, -1), 6), Some(5));
+        assert_eq!(grid.get_tile(&Tile::new(1, -1)), Some(&6));
+        assert_eq!(grid.get_tile(&Tile::new(-1, 1)), None);
+
+        *grid.get_tile_mut(&Tile::new(1, -1)).unwrap() += 1;
+        assert_eq!(grid.remove_tile(&Tile::new(1, -1)), Some(7));
+        assert_eq!(grid.get_tile(&Tile::new(1, -1)), None);
+    }
+
+    #[test]
+    pub fn out_of_bounds_test() {
+        let grid: DenseHexGrid<i32, &str, bool> = DenseHexGrid::new(Tile::new(0, 0), Tile::new(2, 2));
+
+        assert_eq!(grid.get_tile(&Tile::new(3, 0)), None);
+        assert_eq!(grid.get_tile(&Tile::new(0, -1)), None);
+        assert_eq!(grid.get_edge(&Edge::new(10, 10, EdgeDirection::East)), None);
+        assert_eq!(grid.get_corner(&Corner::new(-10, 0, CornerDirection::North)), None);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn insert_out_of_bounds_test() {
+        let mut grid: DenseHexGrid<i32, &str, bool> = DenseHexGrid::new(Tile::new(0, 0), Tile::new(2, 2));
+        grid.insert_tile(Tile::new(3, 3), 1);
+    }
+
+    #[test]
+    pub fn border_edges_and_corners_test() {
+        // Every edge and corner of every tile must fit, even on the border of the grid.
+        let mut grid: DenseHexGrid<i32, Edge, Corner> = DenseHexGrid::new(Tile::new(0, 0), Tile::new(1, 1));
+        let tiles = [Tile::new(0, 0), Tile::new(1, 0), Tile::new(0, 1), Tile::new(1, 1)];
+
+        for tile in tiles.iter() {
+            for edge in tile.get_edge_neighbors() {
+                grid.insert_edge(edge, edge);
+            }
+            for corner in tile.get_corner_neighbors() {
+                grid.insert_corner(corner, corner);
+            }
+        }
+
+        for tile in tiles.iter() {
+            assert_eq!(grid.get_edge_neighbors(tile).len(), 6);
+            assert_eq!(grid.get_corner_neighbors(tile).len(), 6);
+        }
+        for (edge, data) in grid.iter_edges() {
+            assert_eq!(edge, *data);
+        }
+        for (corner, data) in grid.iter_corners() {
+            assert_eq!(corner, *data);
+        }
+    }
+
+    #[test]
+    pub fn neighbors_match_hexgrid_test() {
+        let mut grid: HexGrid<i32, &str, bool> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), 0);
+        grid.tiles.insert(Tile::new(1, 0), 1);
+        grid.tiles.insert(Tile::new(-1, 0), 2);
+        grid.edges.insert(Edge::new(0, 0, EdgeDirection::East), "0");
+        grid.edges.insert(Edge::new(0, 0, EdgeDirection::Northeast), "1");
+        grid.corners.insert(Corner::new(0, 0, CornerDirection::North), false);
+        grid.corners.insert(Corner::new(0, 0, CornerDirection::Southwest), true);
+
+        let dense = DenseHexGrid::from(grid.clone());
+        let tile = Tile::new(0, 0);
+
+        let mut expected = grid.get_tile_neighbors(&tile);
+        let mut actual = dense.get_tile_neighbors(&tile);
+        expected.sort_by_key(|(c, _)| (c.x, c.y));
+        actual.sort_by_key(|(c, _)| (c.x, c.y));
+        assert_eq!(expected, actual);
+
+        assert_eq!(grid.get_edge_neighbors(&tile).len(), dense.get_edge_neighbors(&tile).len());
+        assert_eq!(grid.get_corner_neighbors(&tile).len(), dense.get_corner_neighbors(&tile).len());
+    }
+
+    #[test]
+    pub fn conversion_test() {
+        let mut grid: HexGrid<i32, &str, bool> = HexGrid::new();
+        grid.tiles.insert(Tile::new(3, -4), 0);
+        grid.tiles.insert(Tile::new(-5, 2), 1);
+        grid.edges.insert(Edge::new(3, -4, EdgeDirection::Southwest), "0");
+        grid.edges.insert(Edge::new(-5, 2, EdgeDirection::West), "1");
+        grid.corners.insert(Corner::new(3, -4, CornerDirection::Southeast), false);
+        grid.corners.insert(Corner::new(-5, 2, CornerDirection::Northwest), true);
+
+        let dense = DenseHexGrid::from(grid.clone());
+        assert_eq!(dense.iter_tiles().count(), 2);
+        assert_eq!(dense.iter_edges().count(), 2);
+        assert_eq!(dense.iter_corners().count(), 2);
+
+        let grid2 = HexGrid::from(dense);
+        assert_eq!(grid, grid2);
+    }
+}
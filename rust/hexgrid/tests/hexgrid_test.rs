@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod hexgrid_tests {
-    use hexgrid::HexGrid;
+    use hexgrid::{HexGrid, Grid};
     use hexgrid::hex_coordinates::{Tile, Corner, CornerDirection, Edge, EdgeDirection};
 
     #[test]
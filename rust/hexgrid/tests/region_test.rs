@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod region_tests {
+    use hexgrid::HexGrid;
+    use hexgrid::hex_coordinates::{HexCoord, Tile};
+
+    /// A hexagon of radius 2 (19 tiles, like the base game), centered on (0, 0).
+    /// Every tile is `true` (land) except those listed in `water`.
+    fn make_island(water: &[Tile]) -> HexGrid<bool, (), ()> {
+        let mut grid = HexGrid::new();
+        for x in -2i32..=2 {
+            for y in -2..=2 {
+                if (x + y).abs() <= 2 {
+                    let tile = Tile::new(x, y);
+                    grid.tiles.insert(tile, !water.contains(&tile));
+                }
+            }
+        }
+        grid
+    }
+
+    #[test]
+    pub fn single_tile_test() {
+        let mut grid: HexGrid<bool, (), ()> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), true);
+
+        assert_eq!(grid.boundary_edges(|t| *t).len(), 6);
+        assert_eq!(grid.boundary_corners(|t| *t).len(), 6);
+        assert_eq!(grid.holes(|t| *t).len(), 0);
+
+        let perimeters = grid.perimeters(|t| *t);
+        assert_eq!(perimeters.len(), 1);
+        assert_eq!(perimeters[0].edges.len(), 6);
+        assert_eq!(perimeters[0].corners.len(), 6);
+        assert!(!perimeters[0].hole);
+    }
+
+    #[test]
+    pub fn empty_region_test() {
+        let grid = make_island(&[]);
+
+        assert_eq!(grid.boundary_edges(|t| !*t).len(), 0);
+        assert_eq!(grid.boundary_corners(|t| !*t).len(), 0);
+        assert_eq!(grid.perimeters(|t| !*t).len(), 0);
+        assert_eq!(grid.holes(|t| !*t).len(), 0);
+    }
+
+    #[test]
+    pub fn coastline_test() {
+        let grid = make_island(&[]);
+
+        assert_eq!(grid.boundary_edges(|t| *t).len(), 30);
+        assert_eq!(grid.boundary_corners(|t| *t).len(), 30);
+
+        let perimeters = grid.perimeters(|t| *t);
+        assert_eq!(perimeters.len(), 1);
+        assert_eq!(perimeters[0].edges.len(), 30);
+        assert!(!perimeters[0].hole);
+    }
+
+    #[test]
+    pub fn perimeter_order_test() {
+        let grid = make_island(&[]);
+        let perimeter = &grid.perimeters(|t| *t)[0];
+        let n = perimeter.edges.len();
+
+        for i in 0..n {
+            let corner = perimeter.corners[i];
+            let edges = corner.get_edge_neighbors();
+            assert!(edges.contains(&perimeter.edges[i]));
+            assert!(edges.contains(&perimeter.edges[(i + 1) % n]));
+        }
+    }
+
+    #[test]
+    pub fn lake_test() {
+        let lake = Tile::new(0, 0);
+        let grid = make_island(&[lake]);
+
+        assert_eq!(grid.boundary_edges(|t| *t).len(), 36);
+
+        let holes = grid.holes(|t| *t);
+        assert_eq!(holes, vec![vec![lake]]);
+
+        let perimeters = grid.perimeters(|t| *t);
+        assert_eq!(perimeters.len(), 2);
+        let hole_loops: Vec<_> = perimeters.iter().filter(|p| p.hole).collect();
+        assert_eq!(hole_loops.len(), 1);
+        assert_eq!(hole_loops[0].edges.len(), 6);
+    }
+
+    #[test]
+    pub fn missing_tile_is_hole_test() {
+        let mut grid = make_island(&[]);
+        grid.tiles.remove(&Tile::new(0, 0));
+
+        assert_eq!(grid.holes(|t| *t), vec![vec![Tile::new(0, 0)]]);
+    }
+
+    #[test]
+    pub fn bay_is_not_hole_test() {
+        // Water on the edge of the island, connected to the outside.
+        let grid = make_island(&[Tile::new(2, 0), Tile::new(1, 0), Tile::new(0, 0)]);
+
+        assert_eq!(grid.holes(|t| *t).len(), 0);
+        let perimeters = grid.perimeters(|t| *t);
+        assert_eq!(perimeters.len(), 1);
+        assert!(!perimeters[0].hole);
+    }
+
+    #[test]
+    pub fn two_islands_test() {
+        let mut grid: HexGrid<bool, (), ()> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), true);
+        grid.tiles.insert(Tile::new(1, 0), false);
+        grid.tiles.insert(Tile::new(2, 0), true);
+
+        let perimeters = grid.perimeters(|t| *t);
+        assert_eq!(perimeters.len(), 2);
+        assert!(perimeters.iter().all(|p| !p.hole && p.edges.len() == 6));
+    }
+}
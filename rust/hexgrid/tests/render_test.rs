@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod render_tests {
+    use hexgrid::HexGrid;
+    use hexgrid::render::render_text;
+    use hexgrid::hex_coordinates::{Tile, Edge, EdgeDirection, Corner, CornerDirection};
+
+    #[test]
+    pub fn empty_test() {
+        let grid: HexGrid<&str, char, char> = HexGrid::new();
+        assert_eq!(render_text(&grid), "");
+    }
+
+    #[test]
+    pub fn single_tile_test() {
+        let mut grid: HexGrid<&str, char, char> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), "Wh 8");
+
+        let expected = [
+            "    .",
+            "  /   \\",
+            ".       .",
+            "| Wh 8  |",
+            ".       .",
+            "  \\   /",
+            "    .",
+        ].join("\n");
+        assert_eq!(render_text(&grid), expected);
+    }
+
+    #[test]
+    pub fn neighbors_share_outline_test() {
+        let mut grid: HexGrid<&str, char, char> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), "A");
+        grid.tiles.insert(Tile::new(1, 0), "B");
+        grid.tiles.insert(Tile::new(0, 1), "C");
+
+        let expected = [
+            "        .",
+            "      /   \\",
+            "    .       .",
+            "    |   C   |",
+            "    .       .",
+            "  /   \\   /   \\",
+            ".       .       .",
+            "|   A   |   B   |",
+            ".       .       .",
+            "  \\   /   \\   /",
+            "    .       .",
+        ].join("\n");
+        assert_eq!(render_text(&grid), expected);
+    }
+
+    #[test]
+    pub fn edges_and_corners_test() {
+        let mut grid: HexGrid<&str, char, char> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), "");
+        grid.edges.insert(Edge::new(0, 0, EdgeDirection::Northwest), 'r');
+        grid.edges.insert(Edge::new(0, 0, EdgeDirection::West), 's');
+        grid.edges.insert(Edge::new(0, 0, EdgeDirection::Southeast), 't');
+        grid.corners.insert(Corner::new(0, 0, CornerDirection::North), 'N');
+        grid.corners.insert(Corner::new(0, 0, CornerDirection::Southwest), 'W');
+
+        let expected = [
+            "    N",
+            "  r   \\",
+            ".       .",
+            "s       |",
+            "W       .",
+            "  \\   t",
+            "    .",
+        ].join("\n");
+        assert_eq!(render_text(&grid), expected);
+    }
+
+    #[test]
+    pub fn long_label_test() {
+        let mut grid: HexGrid<String, (), ()> = HexGrid::new();
+        grid.tiles.insert(Tile::new(0, 0), "123456789".to_string());
+
+        let lines: Vec<String> = render_text(&grid).lines().map(|l| l.to_string()).collect();
+        assert_eq!(lines[3], "|1234567|");
+    }
+}